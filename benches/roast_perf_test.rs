@@ -1,15 +1,15 @@
 // Allow unused code, clippy thinks we aren't using vars imported from sort.rs
-#![allow(dead_code,unused_imports)]
+#![allow(dead_code, unused_imports)]
 
-use std::path::PathBuf;
 use std::hint::black_box;
+use std::path::PathBuf;
 
 use criterion::BenchmarkId;
 use criterion::{criterion_group, criterion_main, Criterion};
 
 #[path = "../src/sort.rs"]
 mod sort;
-use crate::sort::sort_files;
+use crate::sort::{sort_files, FileOptions, PathFilter, SortMode, SortOptions};
 
 #[path = "../src/lines.rs"]
 mod lines;
@@ -18,39 +18,59 @@ use crate::lines::LineEnding;
 #[path = "../src/formatter.rs"]
 mod formatter;
 
+#[path = "../src/diff.rs"]
+mod diff;
+
+#[path = "../src/filters.rs"]
+mod filters;
+
+#[path = "../src/parse_error.rs"]
+mod parse_error;
+
+#[path = "../src/partial.rs"]
+mod partial;
+
+#[path = "../src/order.rs"]
+mod order;
+
+#[path = "../src/collate.rs"]
+mod collate;
+
 const SAMPLE_SIZE: usize = 10;
 
+fn options(sort_arrays: bool) -> SortOptions {
+    SortOptions {
+        line_ending: LineEnding::Lf,
+        sort_arrays,
+        ..Default::default()
+    }
+}
+
 fn sort_fn(c: &mut Criterion) {
-    let files = [
-        "a_1mb",
-        "b_5mb",
-        "c_10mb",
-        "d_25mb",
-    ];
+    let files = ["a_1mb", "b_5mb", "c_10mb", "d_25mb"];
 
     let mut group = c.benchmark_group("file_size");
     group.significance_level(0.1).sample_size(SAMPLE_SIZE);
 
     for test_file in files.iter() {
         group.bench_with_input(
-            BenchmarkId::from_parameter(test_file), 
-            test_file, 
-            |b, b_test_file|{
+            BenchmarkId::from_parameter(test_file),
+            test_file,
+            |b, b_test_file| {
                 b.iter(|| {
                     let path = PathBuf::from(format!("./benches/data/{b_test_file}.json"));
-                    if !path.exists() { panic!("Test data not found: {:?}", path.to_str()) }
+                    if !path.exists() {
+                        panic!("Test data not found: {:?}", path.to_str())
+                    }
                     // Fn being benchmarked
                     sort_files(
-                        &black_box(vec![path.to_owned()]), 
-                        black_box(&LineEnding::Lf), 
-                        black_box(false),
-                        black_box(false),
-                        black_box(1),
-                        black_box(false)
+                        &black_box(vec![path.to_owned()]),
+                        &PathFilter::default(),
+                        black_box(SortMode::Write),
+                        |_| Ok(FileOptions::Sort(black_box(options(false)))),
                     )
-
                 });
-            }
+            },
         );
     }
 
@@ -62,33 +82,27 @@ fn sort_arrays(c: &mut Criterion) {
     let mut group = c.benchmark_group("sort_arrays");
     group.significance_level(0.1).sample_size(SAMPLE_SIZE);
 
-    group.bench_function("don't sort arrays", |b| 
+    group.bench_function("don't sort arrays", |b| {
         b.iter(||
             // Fn being benchmarked
             sort_files(
-                &files, 
-                black_box(&LineEnding::Lf), 
-                black_box(false),
-                black_box(false),
-                black_box(1),
-                black_box(false)
-            )
-        )
-    );
-
-    group.bench_function("do sort arrays", |b| 
+                &files,
+                &PathFilter::default(),
+                black_box(SortMode::Write),
+                |_| Ok(FileOptions::Sort(black_box(options(false))))
+            ))
+    });
+
+    group.bench_function("do sort arrays", |b| {
         b.iter(||
             // Fn being benchmarked
             sort_files(
-                &files, 
-                black_box(&LineEnding::Lf), 
-                black_box(false),
-                black_box(true),
-                black_box(1),
-                black_box(false)
-            )
-        )
-    );
+                &files,
+                &PathFilter::default(),
+                black_box(SortMode::Write),
+                |_| Ok(FileOptions::Sort(black_box(options(true))))
+            ))
+    });
 }
 
 criterion_group!(benches, sort_fn, sort_arrays);
@@ -0,0 +1,244 @@
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// How to compare keys and strings in arrays
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum CollationMode {
+    /// Keys in byte order, strings in arrays ignoring case. Strings that only differ by case keep their order
+    #[default]
+    Default,
+    /// Byte order, so uppercase letters sort before lowercase, and non-ASCII characters after `z`
+    Byte,
+    /// Ignoring case, then in byte order when two strings only differ by case
+    IgnoreCase,
+    /// Ignoring case and accents, so `été` sorts with `ete`. Ties are ordered by accents, then by case
+    Unicode,
+}
+
+impl CollationMode {
+    pub fn name(&self) -> &'static str {
+        match self {
+            CollationMode::Default => "default",
+            CollationMode::Byte => "byte",
+            CollationMode::IgnoreCase => "ignore-case",
+            CollationMode::Unicode => "unicode",
+        }
+    }
+}
+
+/// How strings are compared when sorting object keys and arrays of strings
+///
+///  * `mode` - whether case and accents are ignored, see [CollationMode]
+///  * `natural` - compare runs of digits by their numeric value, see [natural_cmp]
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Collation {
+    pub mode: CollationMode,
+    pub natural: bool,
+}
+
+impl Collation {
+    /// True if keys are sorted in byte order, as they are stored in a [serde_json::Map]
+    pub fn is_byte_order(&self) -> bool {
+        matches!(self.mode, CollationMode::Default | CollationMode::Byte) && !self.natural
+    }
+
+    /// Compare two object keys
+    pub fn cmp_keys(&self, a: &str, b: &str) -> Ordering {
+        match self.mode {
+            CollationMode::Default => self.cmp_with(CollationMode::Byte, a, b),
+            mode => self.cmp_with(mode, a, b),
+        }
+    }
+
+    /// Compare two strings in an array.
+    ///
+    /// With [CollationMode::Default], strings that only differ by case are equal, so a stable sort keeps
+    /// them in their input order. Every other mode orders them deterministically.
+    pub fn cmp_strings(&self, a: &str, b: &str) -> Ordering {
+        match self.mode {
+            // natural order compares keys and arrays the same way
+            CollationMode::Default if self.natural => self.cmp_with(CollationMode::Byte, a, b),
+            // strings that only differ by case keep their order
+            CollationMode::Default => a.to_lowercase().cmp(&b.to_lowercase()),
+            mode => self.cmp_with(mode, a, b),
+        }
+    }
+
+    fn cmp_with(&self, mode: CollationMode, a: &str, b: &str) -> Ordering {
+        let cmp = |x: &str, y: &str| match self.natural {
+            true => natural_cmp(x, y),
+            false => x.cmp(y),
+        };
+
+        match mode {
+            CollationMode::Default | CollationMode::Byte => cmp(a, b),
+            CollationMode::IgnoreCase => {
+                cmp(&a.to_lowercase(), &b.to_lowercase()).then_with(|| cmp(a, b))
+            }
+            CollationMode::Unicode => cmp(&fold(a), &fold(b))
+                .then_with(|| cmp(&a.to_lowercase(), &b.to_lowercase()))
+                .then_with(|| cmp(a, b)),
+        }
+    }
+}
+
+/// Lowercase `s` without any accents, e.g. `Ärger` -> `arger`.
+/// Letters that are not composed with an accent are replaced with their usual base letters, e.g. `ß` -> `ss`
+fn fold(s: &str) -> Cow<'_, str> {
+    if s.bytes().all(|b| b.is_ascii() && !b.is_ascii_uppercase()) {
+        return Cow::Borrowed(s);
+    }
+
+    let mut folded = String::with_capacity(s.len());
+    for c in s
+        .nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+    {
+        match c {
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            'ø' => folded.push('o'),
+            'đ' | 'ð' => folded.push('d'),
+            'ł' => folded.push('l'),
+            'ı' => folded.push('i'),
+            'þ' => folded.push_str("th"),
+            c => folded.push(c),
+        }
+    }
+    Cow::Owned(folded)
+}
+
+/// Compare strings in natural order, so runs of ASCII digits are compared by their numeric value,
+/// e.g. `item2` < `item10` and `v1.9` < `v1.10`. Everything else is compared in byte order.
+///
+/// Numbers that only differ by leading zeros are ordered with the shortest first, e.g. `1` < `01`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (a_bytes, b_bytes) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+
+    while i < a_bytes.len() && j < b_bytes.len() {
+        let (x, y) = (a_bytes[i], b_bytes[j]);
+        if !(x.is_ascii_digit() && y.is_ascii_digit()) {
+            match x.cmp(&y) {
+                Ordering::Equal => (i, j) = (i + 1, j + 1),
+                other => return other,
+            }
+            continue;
+        }
+
+        let a_end = digits_end(a_bytes, i);
+        let b_end = digits_end(b_bytes, j);
+        let a_num = trim_zeros(&a_bytes[i..a_end]);
+        let b_num = trim_zeros(&b_bytes[j..b_end]);
+        let by_value = a_num.len().cmp(&b_num.len()).then(a_num.cmp(b_num));
+        if by_value != Ordering::Equal {
+            return by_value;
+        }
+        (i, j) = (a_end, b_end);
+    }
+
+    (a_bytes.len() - i)
+        .cmp(&(b_bytes.len() - j))
+        .then_with(|| a.len().cmp(&b.len()))
+        .then_with(|| a.cmp(b))
+}
+
+fn digits_end(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .map_or(bytes.len(), |p| start + p)
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let zeros = digits.iter().take_while(|d| **d == b'0').count();
+    &digits[zeros..]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sorted(list: &[&str]) -> Vec<String> {
+        let mut list: Vec<String> = list.iter().map(|s| s.to_string()).collect();
+        list.sort_by(|a, b| natural_cmp(a, b));
+        list
+    }
+
+    #[test]
+    fn natural_order() {
+        assert_eq!(
+            sorted(&["item10", "item2", "item1", "item", "item02"]),
+            vec!["item", "item1", "item2", "item02", "item10"]
+        );
+        assert_eq!(
+            sorted(&["v1.10", "v1.9", "v10.0", "v1.9.1"]),
+            vec!["v1.9", "v1.9.1", "v1.10", "v10.0"]
+        );
+        assert_eq!(
+            sorted(&["b", "a10", "a", "10", "9"]),
+            vec!["9", "10", "a", "a10", "b"]
+        );
+    }
+
+    fn sorted_with(mode: CollationMode, list: &[&str]) -> Vec<String> {
+        let collation = Collation {
+            mode,
+            natural: false,
+        };
+        let mut list: Vec<String> = list.iter().map(|s| s.to_string()).collect();
+        list.sort_by(|a, b| collation.cmp_keys(a, b));
+        list
+    }
+
+    #[test]
+    fn collation_modes() {
+        let keys = [
+            "apple", "Zebra", "été", "Ärger", "zoo", "Apple", "ete", "Ete",
+        ];
+        assert_eq!(
+            sorted_with(CollationMode::Byte, &keys),
+            vec!["Apple", "Ete", "Zebra", "apple", "ete", "zoo", "Ärger", "été"]
+        );
+        assert_eq!(
+            sorted_with(CollationMode::IgnoreCase, &keys),
+            vec!["Apple", "apple", "Ete", "ete", "Zebra", "zoo", "Ärger", "été"]
+        );
+        assert_eq!(
+            sorted_with(CollationMode::Unicode, &keys),
+            vec!["Apple", "apple", "Ärger", "Ete", "ete", "été", "Zebra", "zoo"]
+        );
+        assert_eq!(
+            sorted_with(CollationMode::Unicode, &["Straße", "Strasse", "Strand"]),
+            vec!["Strand", "Strasse", "Straße"]
+        );
+    }
+
+    #[test]
+    fn default_collation() {
+        let collation = Collation::default();
+        assert_eq!(collation.cmp_keys("Zebra", "apple"), Ordering::Less);
+        assert_eq!(collation.cmp_strings("Zebra", "apple"), Ordering::Greater);
+        assert_eq!(collation.cmp_strings("apple", "Apple"), Ordering::Equal);
+        assert!(collation.is_byte_order());
+    }
+
+    #[test]
+    fn natural_order_is_total() {
+        assert_eq!(natural_cmp("a01", "a1"), Ordering::Greater);
+        assert_eq!(natural_cmp("a1", "a01"), Ordering::Less);
+        assert_eq!(natural_cmp("a1", "a1"), Ordering::Equal);
+        assert_eq!(
+            natural_cmp("99999999999999999999999", "100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+}
@@ -0,0 +1,591 @@
+use ec4rs::property::{EndOfLine, FinalNewline, IndentSize, IndentStyle};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::collate::{Collation, CollationMode};
+use crate::git::Attributes;
+use crate::lines::LineEnding;
+use crate::order::{ArrayOrder, KeyOrder};
+use crate::sort::{FileOptions, SortOptions};
+
+/// Config file names, in order of precedence when several exist in the same directory
+pub const CONFIG_FILE_NAMES: &[&str] = &["roast.toml", ".roastrc"];
+
+const INDENT_SIZE_SPACE: usize = 2;
+const INDENT_SIZE_TAB: usize = 1;
+
+/// Sorting options that may be set on the command line, in a config file or in an override section.
+/// Any option that is not set falls back to the next source, and finally to the default.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct PartialOptions {
+    pub arrays: Option<bool>,
+    pub indentation_count: Option<usize>,
+    #[serde(deserialize_with = "deserialize_line_ending")]
+    pub line_ending: Option<LineEnding>,
+    pub spaces: Option<bool>,
+    pub final_newline: Option<bool>,
+    pub natural: Option<bool>,
+    pub collation: Option<CollationMode>,
+    /// `[[pin]]` tables of keys to pin first or last in objects
+    pub pin: Option<KeyOrder>,
+    /// `[[array]]` tables of strategies for sorting the arrays at specific paths
+    pub array: Option<ArrayOrder>,
+    pub identity_keys: Option<bool>,
+}
+
+impl PartialOptions {
+    /// Set any options that are set in `other`
+    fn merge(&mut self, other: &PartialOptions) {
+        if other.arrays.is_some() {
+            self.arrays = other.arrays;
+        }
+        if other.indentation_count.is_some() {
+            self.indentation_count = other.indentation_count;
+        }
+        if other.line_ending.is_some() {
+            self.line_ending.clone_from(&other.line_ending);
+        }
+        if other.spaces.is_some() {
+            self.spaces = other.spaces;
+        }
+        if other.final_newline.is_some() {
+            self.final_newline = other.final_newline;
+        }
+        if other.natural.is_some() {
+            self.natural = other.natural;
+        }
+        if other.collation.is_some() {
+            self.collation = other.collation;
+        }
+        if other.pin.is_some() {
+            self.pin.clone_from(&other.pin);
+        }
+        if other.array.is_some() {
+            self.array.clone_from(&other.array);
+        }
+        if other.identity_keys.is_some() {
+            self.identity_keys = other.identity_keys;
+        }
+    }
+
+    /// Options to sort with, using the defaults for anything that is not set
+    pub fn to_sort_options(&self) -> SortOptions {
+        let use_spaces = self.spaces.unwrap_or(false);
+        let indents = match self.indentation_count {
+            Some(count) if count > 0 => count,
+            _ if use_spaces => INDENT_SIZE_SPACE,
+            _ => INDENT_SIZE_TAB,
+        };
+
+        SortOptions {
+            line_ending: self
+                .line_ending
+                .clone()
+                .unwrap_or(LineEnding::SystemDefault),
+            use_spaces,
+            sort_arrays: self.arrays.unwrap_or(false),
+            indents,
+            final_newline: self.final_newline.unwrap_or(true),
+            changed_lines: None,
+            key_order: self.pin.clone().unwrap_or_default(),
+            collation: Collation {
+                mode: self.collation.unwrap_or_default(),
+                natural: self.natural.unwrap_or(false),
+            },
+            array_order: self.array.clone().unwrap_or_default(),
+            identity_keys: self.identity_keys.unwrap_or(false),
+        }
+    }
+}
+
+/// Read the `.editorconfig` properties that apply to the file at `path`.
+///
+/// `indent_size` is returned separately, as it only applies when indenting with spaces.
+/// With tabs it is the width of a tab, roast always uses one tab per level.
+fn editorconfig_options(path: &Path) -> Result<(PartialOptions, Option<usize>), String> {
+    let props = ec4rs::properties_of(path).map_err(|e| format!(".editorconfig: {e}"))?;
+
+    let spaces = match props.get::<IndentStyle>() {
+        Ok(IndentStyle::Spaces) => Some(true),
+        Ok(IndentStyle::Tabs) => Some(false),
+        Err(_) => None,
+    };
+    let indent_size = match props.get::<IndentSize>() {
+        Ok(IndentSize::Value(size)) => Some(size),
+        _ => None,
+    };
+    let line_ending = match props.get::<EndOfLine>() {
+        Ok(EndOfLine::Lf) => Some(LineEnding::Lf),
+        Ok(EndOfLine::CrLf) => Some(LineEnding::CrLf),
+        Ok(EndOfLine::Cr) => Some(LineEnding::Cr),
+        Err(_) => None,
+    };
+    let final_newline = match props.get::<FinalNewline>() {
+        Ok(FinalNewline::Value(v)) => Some(v),
+        Err(_) => None,
+    };
+
+    let options = PartialOptions {
+        line_ending,
+        spaces,
+        final_newline,
+        ..Default::default()
+    };
+    Ok((options, indent_size))
+}
+
+fn deserialize_line_ending<'de, D>(deserializer: D) -> Result<Option<LineEnding>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    const VARIANTS: &[&str] = &["cr", "crlf", "lf", "original"];
+
+    let s = String::deserialize(deserializer)?;
+    match s.to_lowercase().as_str() {
+        "original" => Ok(Some(LineEnding::SystemDefault)),
+        name if VARIANTS.contains(&name) => Ok(LineEnding::from_str(name).ok()),
+        _ => Err(D::Error::unknown_variant(&s, VARIANTS)),
+    }
+}
+
+/// Options applied to files matching any of the `files` glob patterns
+struct Override {
+    patterns: Vec<String>,
+    globs: GlobSet,
+    options: PartialOptions,
+}
+
+/// A parsed `roast.toml` or `.roastrc` config file
+struct Config {
+    path: PathBuf,
+    options: PartialOptions,
+    overrides: Vec<Override>,
+}
+
+impl Config {
+    fn load(path: &Path) -> Result<Config, String> {
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+        // .roastrc is JSON, like other rc files. Both are converted to a JSON Value so
+        // they can share the same validation.
+        let value: Value = if path.extension().is_some_and(|e| e == "toml") {
+            toml::from_str(&contents).map_err(|e| e.to_string())?
+        } else {
+            serde_json::from_str(&contents).map_err(|e| e.to_string())?
+        };
+
+        let Value::Object(mut table) = value else {
+            return Err("expected a table of options".into());
+        };
+
+        let overrides = match table.remove("overrides") {
+            Some(Value::Array(list)) => list
+                .into_iter()
+                .map(parse_override)
+                .collect::<Result<Vec<Override>, String>>()?,
+            Some(_) => return Err("`overrides` must be a list of tables".into()),
+            None => vec![],
+        };
+
+        Ok(Config {
+            path: path.to_path_buf(),
+            options: parse_options(table)?,
+            overrides,
+        })
+    }
+
+    fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+}
+
+fn parse_options(table: Map<String, Value>) -> Result<PartialOptions, String> {
+    serde_json::from_value(Value::Object(table)).map_err(|e| e.to_string())
+}
+
+fn parse_override(value: Value) -> Result<Override, String> {
+    let Value::Object(mut table) = value else {
+        return Err("`overrides` must be a list of tables".into());
+    };
+
+    let patterns: Vec<String> = match table.remove("files") {
+        Some(Value::String(s)) => vec![s],
+        Some(files) => serde_json::from_value(files)
+            .map_err(|_| "`files` must be a glob pattern or a list of glob patterns")?,
+        None => return Err("override is missing `files`".into()),
+    };
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns.iter() {
+        // patterns without a separator match the file name in any directory
+        let pattern = pattern.trim_start_matches('/');
+        let pattern = if pattern.contains('/') {
+            pattern.to_string()
+        } else {
+            format!("**/{pattern}")
+        };
+        let glob = GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| e.to_string())?;
+        builder.add(glob);
+    }
+
+    Ok(Override {
+        patterns,
+        globs: builder.build().map_err(|e| e.to_string())?,
+        options: parse_options(table)?,
+    })
+}
+
+/// Effective options for a single file, and where they came from
+pub struct Resolved {
+    pub options: SortOptions,
+    pub config_path: Option<PathBuf>,
+    pub overrides: Vec<String>,
+    /// Why the file should not be sorted, from `.gitattributes`
+    pub skip: Option<String>,
+}
+
+impl Resolved {
+    pub fn file_options(self) -> FileOptions {
+        match self.skip {
+            Some(reason) => FileOptions::Skip(reason),
+            None => FileOptions::Sort(self.options),
+        }
+    }
+}
+
+impl Display for Resolved {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.config_path {
+            Some(p) => writeln!(f, "# config file: {}", p.display())?,
+            None => writeln!(f, "# config file: none")?,
+        }
+        if !self.overrides.is_empty() {
+            writeln!(f, "# overrides: {}", self.overrides.join(", "))?;
+        }
+        if let Some(reason) = &self.skip {
+            writeln!(f, "# skipped: {reason}")?;
+        }
+        writeln!(f, "arrays = {}", self.options.sort_arrays)?;
+        writeln!(f, "indentationCount = {}", self.options.indents)?;
+        writeln!(f, "lineEnding = \"{}\"", self.options.line_ending.name())?;
+        writeln!(f, "spaces = {}", self.options.use_spaces)?;
+        writeln!(f, "finalNewline = {}", self.options.final_newline)?;
+        writeln!(f, "natural = {}", self.options.collation.natural)?;
+        writeln!(f, "collation = \"{}\"", self.options.collation.mode.name())?;
+        writeln!(f, "identityKeys = {}", self.options.identity_keys)?;
+        write!(f, "{}", self.options.key_order)?;
+        write!(f, "{}", self.options.array_order)
+    }
+}
+
+/// Resolves the options for each file from the nearest config file and the command line.
+///
+/// Options are applied in order of precedence:
+///
+/// 1. command line flags
+/// 2. `roast-arrays` in `.gitattributes`
+/// 3. matching `overrides` in the config file, later sections take precedence
+/// 4. the top level options in the config file
+/// 5. matching sections in `.editorconfig` files
+///
+/// Files with a `roast=false` or `linguist-generated` attribute are skipped.
+pub struct Settings {
+    cli: PartialOptions,
+    // directory -> nearest config file in or above it
+    configs: HashMap<PathBuf, Option<Rc<Config>>>,
+    attributes: Option<Attributes>,
+}
+
+impl Settings {
+    pub fn new(cli: PartialOptions) -> Self {
+        Settings {
+            cli,
+            configs: HashMap::new(),
+            attributes: Attributes::open(),
+        }
+    }
+
+    /// Resolve the options for the file at `path`
+    pub fn resolve(&mut self, path: &Path) -> Result<Resolved, String> {
+        let path = absolute(path);
+        let dir = path.parent().unwrap_or(Path::new("."));
+        self.resolve_in(dir, Some(&path))
+    }
+
+    /// Resolve the options for JSON read from stdin. Overrides do not apply.
+    pub fn resolve_stdin(&mut self) -> Result<Resolved, String> {
+        let cwd = absolute(Path::new("."));
+        self.resolve_in(&cwd, None)
+    }
+
+    fn resolve_in(&mut self, dir: &Path, path: Option<&Path>) -> Result<Resolved, String> {
+        let config = self.find_config(dir)?;
+
+        let (mut options, indent_size) = match path {
+            Some(p) => editorconfig_options(p)?,
+            None => (PartialOptions::default(), None),
+        };
+        let mut overrides = vec![];
+        if let Some(config) = &config {
+            options.merge(&config.options);
+
+            if let Some(relative) = path.and_then(|p| p.strip_prefix(config.dir()).ok()) {
+                for o in config
+                    .overrides
+                    .iter()
+                    .filter(|o| o.globs.is_match(relative))
+                {
+                    options.merge(&o.options);
+                    overrides.push(o.patterns.join(" "));
+                }
+            }
+        }
+
+        let attributes = match (&self.attributes, path) {
+            (Some(a), Some(p)) => a.lookup(p),
+            _ => Default::default(),
+        };
+        options.merge(&PartialOptions {
+            arrays: attributes.arrays,
+            ..Default::default()
+        });
+        options.merge(&self.cli);
+        if options.indentation_count.is_none() && options.spaces == Some(true) {
+            options.indentation_count = indent_size;
+        }
+
+        Ok(Resolved {
+            options: options.to_sort_options(),
+            config_path: config.map(|c| c.path.clone()),
+            overrides,
+            skip: attributes.skip,
+        })
+    }
+
+    fn find_config(&mut self, dir: &Path) -> Result<Option<Rc<Config>>, String> {
+        if let Some(config) = self.configs.get(dir) {
+            return Ok(config.clone());
+        }
+
+        let mut config = None;
+        for name in CONFIG_FILE_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                log::debug!("Using config file: {:?}", candidate.to_str());
+                let loaded = Config::load(&candidate)
+                    .map_err(|e| format!("{}: {e}", candidate.display()))?;
+                config = Some(Rc::new(loaded));
+                break;
+            }
+        }
+
+        if config.is_none() {
+            if let Some(parent) = dir.parent() {
+                config = self.find_config(parent)?;
+            }
+        }
+
+        self.configs.insert(dir.to_path_buf(), config.clone());
+        Ok(config)
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if let Ok(p) = path.canonicalize() {
+        return p;
+    }
+
+    // file may not exist, resolve the parent directory instead
+    let full = env::current_dir().unwrap_or_default().join(path);
+    match (full.parent().map(Path::canonicalize), full.file_name()) {
+        (Some(Ok(parent)), Some(name)) => parent.join(name),
+        _ => full,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn no_config() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let file = write(tempdir.path(), "a.json", "{}");
+
+        let mut settings = Settings::new(PartialOptions::default());
+        let resolved = settings.resolve(&file).unwrap();
+
+        assert!(resolved.config_path.is_none());
+        assert!(!resolved.options.use_spaces);
+        assert!(!resolved.options.sort_arrays);
+        assert_eq!(resolved.options.indents, INDENT_SIZE_TAB);
+    }
+
+    #[test]
+    fn nearest_config_and_overrides() {
+        let tempdir = tempfile::tempdir().unwrap();
+        write(
+            tempdir.path(),
+            "roast.toml",
+            r#"
+spaces = true
+lineEnding = "lf"
+
+[[overrides]]
+files = ["i18n/**"]
+arrays = true
+
+[[overrides]]
+files = "*.schema.json"
+indentationCount = 4
+"#,
+        );
+        let plain = write(tempdir.path(), "a/plain.json", "{}");
+        let i18n = write(tempdir.path(), "i18n/de/de.json", "{}");
+        let schema = write(tempdir.path(), "a/b/x.schema.json", "{}");
+
+        let mut settings = Settings::new(PartialOptions::default());
+
+        let resolved = settings.resolve(&plain).unwrap();
+        assert!(resolved.options.use_spaces);
+        assert!(!resolved.options.sort_arrays);
+        assert_eq!(resolved.options.indents, INDENT_SIZE_SPACE);
+        assert_eq!(resolved.options.line_ending.name(), "lf");
+
+        let resolved = settings.resolve(&i18n).unwrap();
+        assert!(resolved.options.sort_arrays);
+        assert_eq!(resolved.overrides, vec!["i18n/**"]);
+
+        let resolved = settings.resolve(&schema).unwrap();
+        assert!(!resolved.options.sort_arrays);
+        assert_eq!(resolved.options.indents, 4);
+    }
+
+    #[test]
+    fn cli_takes_precedence() {
+        let tempdir = tempfile::tempdir().unwrap();
+        write(tempdir.path(), ".roastrc", r#"{"indentationCount": 4}"#);
+        let file = write(tempdir.path(), "a.json", "{}");
+
+        let cli = PartialOptions {
+            indentation_count: Some(3),
+            ..Default::default()
+        };
+        let mut settings = Settings::new(cli);
+        let resolved = settings.resolve(&file).unwrap();
+
+        assert_eq!(resolved.options.indents, 3);
+    }
+
+    #[test]
+    fn editorconfig() {
+        let tempdir = tempfile::tempdir().unwrap();
+        write(
+            tempdir.path(),
+            ".editorconfig",
+            "root = true
+
+[*.json]
+indent_style = space
+indent_size = 4
+end_of_line = crlf
+insert_final_newline = false
+
+[tabs/*.json]
+indent_style = tab
+indent_size = 8
+",
+        );
+        let spaces = write(tempdir.path(), "a.json", "{}");
+        let tabs = write(tempdir.path(), "tabs/b.json", "{}");
+
+        let mut settings = Settings::new(PartialOptions::default());
+
+        let resolved = settings.resolve(&spaces).unwrap();
+        assert!(resolved.options.use_spaces);
+        assert_eq!(resolved.options.indents, 4);
+        assert_eq!(resolved.options.line_ending.name(), "crlf");
+        assert!(!resolved.options.final_newline);
+
+        let resolved = settings.resolve(&tabs).unwrap();
+        assert!(!resolved.options.use_spaces);
+        assert_eq!(resolved.options.indents, 1);
+    }
+
+    #[test]
+    fn config_takes_precedence_over_editorconfig() {
+        let tempdir = tempfile::tempdir().unwrap();
+        write(
+            tempdir.path(),
+            ".editorconfig",
+            "root = true\n[*]\nindent_style = space\nindent_size = 4\n",
+        );
+        write(tempdir.path(), "roast.toml", "indentationCount = 3");
+        let file = write(tempdir.path(), "a.json", "{}");
+
+        let mut settings = Settings::new(PartialOptions::default());
+        let resolved = settings.resolve(&file).unwrap();
+
+        assert!(resolved.options.use_spaces);
+        assert_eq!(resolved.options.indents, 3);
+    }
+
+    #[test]
+    fn editorconfig_indent_size_only_with_spaces() {
+        let tempdir = tempfile::tempdir().unwrap();
+        write(
+            tempdir.path(),
+            ".editorconfig",
+            "root = true\n[tabs/*]\nindent_style = tab\n[size/*]\nindent_size = 4\n",
+        );
+        let tabs = write(tempdir.path(), "tabs/a.json", "{}");
+        let size = write(tempdir.path(), "size/a.json", "{}");
+
+        let mut settings = Settings::new(PartialOptions::default());
+        let resolved = settings.resolve(&size).unwrap();
+        assert!(!resolved.options.use_spaces);
+        assert_eq!(resolved.options.indents, INDENT_SIZE_TAB);
+
+        let mut settings = Settings::new(PartialOptions {
+            spaces: Some(true),
+            ..Default::default()
+        });
+        let resolved = settings.resolve(&tabs).unwrap();
+        assert!(resolved.options.use_spaces);
+        assert_eq!(resolved.options.indents, INDENT_SIZE_SPACE);
+
+        let resolved = settings.resolve(&size).unwrap();
+        assert_eq!(resolved.options.indents, 4);
+    }
+
+    #[test]
+    fn unknown_option() {
+        let tempdir = tempfile::tempdir().unwrap();
+        write(tempdir.path(), "roast.toml", "spacs = true");
+        let file = write(tempdir.path(), "a.json", "{}");
+
+        let mut settings = Settings::new(PartialOptions::default());
+        let error = settings.resolve(&file).err().unwrap();
+
+        assert!(error.contains("unknown field `spacs`"));
+    }
+}
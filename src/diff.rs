@@ -0,0 +1,83 @@
+use colored::*;
+use similar::TextDiff;
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+const CONTEXT_LINES: usize = 3;
+
+/// Create a unified diff between the original and sorted contents of a file.
+///
+/// The `a/` and `b/` header prefixes allow the output to be applied with
+/// `git apply` or `patch -p1`. Returns an empty String if the contents are equal.
+pub fn unified_diff(original: &str, sorted: &str, path: &str) -> String {
+    if original == sorted {
+        return String::new();
+    }
+
+    let path = path.trim_start_matches("./");
+
+    TextDiff::from_lines(original, sorted)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&format!("a/{path}"), &format!("b/{path}"))
+        .to_string()
+}
+
+/// Path of a file as written in a diff header, without the `a/` and `b/` prefixes.
+///
+/// Relative to the root of the git repository containing the file, as `git diff` does, otherwise
+/// relative to the current directory. Files outside both are written without their leading `/`.
+pub fn diff_path(path: &Path) -> String {
+    let full = absolute(path);
+
+    let repo_root = full
+        .parent()
+        .and_then(|dir| git2::Repository::discover(dir).ok())
+        .and_then(|repo| repo.workdir().map(absolute));
+    let current = env::current_dir().map(|dir| absolute(&dir));
+
+    let relative = repo_root
+        .iter()
+        .chain(current.iter())
+        .find_map(|root| full.strip_prefix(root).ok())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| {
+            full.components()
+                .filter(|c| matches!(c, Component::Normal(_)))
+                .collect()
+        });
+
+    let parts: Vec<_> = relative.iter().map(|p| p.to_string_lossy()).collect();
+    parts.join("/")
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if let Ok(p) = path.canonicalize() {
+        return p;
+    }
+    env::current_dir().unwrap_or_default().join(path)
+}
+
+/// Colorize each line of a unified diff. Colors are omitted when stdout is not a terminal.
+pub fn colorize(diff: &str) -> String {
+    diff.split_inclusive('\n')
+        .map(|line| {
+            let (content, eol) = match line.strip_suffix('\n') {
+                Some(l) => (l, "\n"),
+                None => (line, ""),
+            };
+            let colored = if content.starts_with("---") || content.starts_with("+++") {
+                content.bold()
+            } else if content.starts_with("@@") {
+                content.cyan()
+            } else if content.starts_with('+') {
+                content.green()
+            } else if content.starts_with('-') {
+                content.red()
+            } else {
+                content.normal()
+            };
+            format!("{colored}{eol}")
+        })
+        .collect()
+}
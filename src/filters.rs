@@ -0,0 +1,387 @@
+use globset::{Glob, GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+/// Files and directories that are never sorted, unless matched by an include pattern
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    "node_modules",
+    "package.json",
+    "package_lock.json",
+    ".DS_Store",
+    "npm-debug.log",
+    ".svn",
+    "CVS",
+    "config.gypi",
+    ".lock-wscript",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    ".git",
+    "roast.toml",
+];
+
+/// Well known files that contain JSON, without a `.json` extension
+pub const KNOWN_JSON_FILES: &[&str] = &[
+    ".babelrc",
+    ".bowerrc",
+    ".eslintrc",
+    ".htmlhintrc",
+    ".jscsrc",
+    ".jshintrc",
+    ".lintstagedrc",
+    ".nycrc",
+    ".prettierrc",
+    ".roastrc",
+    ".stylelintrc",
+    ".swcrc",
+    ".watchmanconfig",
+];
+
+/// Number of bytes read from the start of a file to guess if it contains JSON
+const SNIFF_SIZE: usize = 512;
+
+/// Name of the file containing gitignore style patterns of files roast should not sort
+pub const IGNORE_FILE_NAME: &str = ".roastignore";
+
+/// A set of glob patterns matched against the components of a path.
+///
+/// Patterns without a `/` match any single file or directory name in the path,
+/// e.g. `node_modules` or `*.min.json`.
+/// Patterns with a `/` match the path relative to the current directory, or any of its parent directories,
+/// e.g. `test/fixtures` or `config/**/*.json`.
+#[derive(Clone, Debug)]
+struct PathGlobs {
+    names: GlobSet,
+    paths: GlobSet,
+    len: usize,
+}
+
+impl PathGlobs {
+    fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self, globset::Error> {
+        let mut names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+
+        for pattern in patterns.iter().map(|p| p.as_ref()) {
+            let pattern = pattern.trim_end_matches('/');
+            if pattern.contains('/') {
+                let pattern = pattern.trim_start_matches("./").trim_start_matches('/');
+                paths.add(GlobBuilder::new(pattern).literal_separator(true).build()?);
+            } else {
+                names.add(Glob::new(pattern)?);
+            }
+        }
+
+        Ok(PathGlobs {
+            names: names.build()?,
+            paths: paths.build()?,
+            len: patterns.len(),
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn is_match(&self, path: &Path) -> bool {
+        let relative = relative_components(path);
+
+        if relative.iter().any(|c| self.names.is_match(c)) {
+            return true;
+        }
+
+        (1..=relative.len()).any(|n| self.paths.is_match(relative[..n].join("/")))
+    }
+}
+
+/// Decides which paths roast should sort, from the built in [DEFAULT_EXCLUDES]
+/// and any `--include` or `--exclude` patterns.
+///
+/// A path is sorted if it matches an include pattern, or there are none,
+/// and it does not match an exclude pattern.
+/// Paths matching an include pattern are sorted even if they match [DEFAULT_EXCLUDES].
+///
+/// Only files that look like JSON are sorted, see [PathFilter::is_json].
+#[derive(Clone, Debug)]
+pub struct PathFilter {
+    defaults: PathGlobs,
+    includes: PathGlobs,
+    excludes: PathGlobs,
+    extensions: Vec<String>,
+    sniff_listed: bool,
+}
+
+impl PathFilter {
+    pub fn new<S: AsRef<str>>(includes: &[S], excludes: &[S]) -> Result<Self, globset::Error> {
+        Ok(PathFilter {
+            defaults: PathGlobs::new(DEFAULT_EXCLUDES)?,
+            includes: PathGlobs::new(includes)?,
+            excludes: PathGlobs::new(excludes)?,
+            extensions: vec!["json".into()],
+            sniff_listed: false,
+        })
+    }
+
+    /// Also treat files with these extensions as JSON
+    pub fn with_extensions<S: AsRef<str>>(mut self, extensions: &[S]) -> Self {
+        self.extensions.extend(
+            extensions
+                .iter()
+                .map(|e| e.as_ref().trim_start_matches('.').to_lowercase()),
+        );
+        self
+    }
+
+    /// Also check the contents of files listed by path, not only those found in directories or by globs.
+    /// For lists of files the user did not choose, e.g. selected from git
+    pub fn sniffing_listed(mut self) -> Self {
+        self.sniff_listed = true;
+        self
+    }
+
+    pub fn sniffs_listed(&self) -> bool {
+        self.sniff_listed
+    }
+
+    /// Does the file at `path` look like JSON.
+    ///
+    /// True for files with a JSON extension and [KNOWN_JSON_FILES].
+    /// Any other file is JSON if it starts with `{` or `[`, only the start of the file is read.
+    pub fn is_json(&self, path: &Path) -> bool {
+        self.has_json_name(path) || starts_like_json(path)
+    }
+
+    /// Do the `contents` of a file that is not on disk, e.g. in the git index, look like JSON.
+    /// See [PathFilter::is_json]
+    pub fn is_json_contents(&self, path: &Path, contents: &[u8]) -> bool {
+        self.has_json_name(path) || starts_with_bracket(contents)
+    }
+
+    /// Is the file at `path` JSON by its name alone, so its contents do not need checking
+    pub fn has_json_name(&self, path: &Path) -> bool {
+        let has_extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| self.extensions.contains(&e.to_lowercase()));
+        let is_known = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| KNOWN_JSON_FILES.contains(&n));
+
+        has_extension || is_known
+    }
+
+    /// Should the file at `path` be skipped
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        if self.excludes.is_match(path) {
+            return true;
+        }
+
+        if self.includes.is_empty() {
+            self.defaults.is_match(path)
+        } else {
+            !self.includes.is_match(path)
+        }
+    }
+
+    /// Should the directory at `path` be skipped, without walking it
+    pub fn is_excluded_dir(&self, path: &Path) -> bool {
+        // a file in a default excluded directory could still be included
+        self.excludes.is_match(path) || (self.includes.is_empty() && self.defaults.is_match(path))
+    }
+}
+
+impl Default for PathFilter {
+    fn default() -> Self {
+        PathFilter::new::<&str>(&[], &[]).expect("default excludes are valid globs")
+    }
+}
+
+/// Characters that make a path argument a glob pattern
+const GLOB_CHARS: &[char] = &['*', '?', '[', '{'];
+
+/// Is the path argument a glob pattern, rather than a literal path
+pub fn is_glob(path: &Path) -> bool {
+    path.to_str().is_some_and(|s| s.contains(GLOB_CHARS))
+}
+
+/// A glob pattern path argument, expanded by walking its base directory
+///
+///  * `base` - leading components of the pattern without any glob characters
+///  * `max_depth` - how deep to walk `base`, unless the pattern contains `**`
+///
+#[derive(Clone, Debug)]
+pub struct GlobPattern {
+    pub base: PathBuf,
+    pub max_depth: Option<usize>,
+    matcher: GlobMatcher,
+}
+
+impl GlobPattern {
+    pub fn new(pattern: &Path) -> Result<Self, globset::Error> {
+        let components: Vec<Component> = pattern.components().collect();
+        let literal_len = components
+            .iter()
+            .take_while(|c| !is_glob(Path::new(c.as_os_str())))
+            .count();
+
+        let base: PathBuf = components[..literal_len].iter().collect();
+        let rest: PathBuf = components[literal_len..].iter().collect();
+        let rest = rest.to_string_lossy().replace('\\', "/");
+        let max_depth = (!rest.contains("**")).then(|| components.len() - literal_len);
+
+        // match against paths relative to the base, so `./` and absolute paths need no special handling
+        let matcher = GlobBuilder::new(&rest)
+            .literal_separator(true)
+            .build()?
+            .compile_matcher();
+
+        Ok(GlobPattern {
+            base,
+            max_depth,
+            matcher,
+        })
+    }
+
+    /// Does a path found by walking [GlobPattern::base] match the pattern
+    pub fn is_match(&self, path: &Path) -> bool {
+        let base = if self.base.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            &self.base
+        };
+
+        match path.strip_prefix(base) {
+            Ok(relative) => self.matcher.is_match(relative),
+            Err(_) => false,
+        }
+    }
+}
+
+fn starts_like_json(path: &Path) -> bool {
+    let mut buf = Vec::with_capacity(SNIFF_SIZE);
+    let read = File::open(path).and_then(|f| f.take(SNIFF_SIZE as u64).read_to_end(&mut buf));
+    read.is_ok() && starts_with_bracket(&buf)
+}
+
+/// Does `contents` start with `{` or `[`, ignoring any byte order mark and whitespace
+fn starts_with_bracket(contents: &[u8]) -> bool {
+    matches!(
+        strip_bom(contents)
+            .iter()
+            .find(|b| !b.is_ascii_whitespace()),
+        Some(b'{') | Some(b'[')
+    )
+}
+
+fn strip_bom(contents: &[u8]) -> &[u8] {
+    contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents)
+}
+
+/// Components of `path` relative to the current directory if possible, without any `.` components
+fn relative_components(path: &Path) -> Vec<String> {
+    let mut relative = PathBuf::from(path);
+    if path.is_absolute() {
+        if let Ok(cwd) = env::current_dir().and_then(|d| d.canonicalize()) {
+            let full = path.canonicalize().unwrap_or(path.to_path_buf());
+            if let Ok(r) = full.strip_prefix(&cwd) {
+                relative = r.to_path_buf();
+            }
+        }
+    }
+
+    relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy().into_owned()),
+            Component::ParentDir => Some("..".into()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_excludes_match_components() {
+        let filter = PathFilter::default();
+
+        assert!(filter.is_excluded(Path::new("node_modules/a/b.json")));
+        assert!(filter.is_excluded(Path::new("./a/node_modules/b.json")));
+        assert!(filter.is_excluded(Path::new("a/package.json")));
+        assert!(filter.is_excluded(Path::new(".git/config")));
+        assert!(!filter.is_excluded(Path::new("CVSreports/a.json")));
+        assert!(!filter.is_excluded(Path::new("node_modules_fan/a.json")));
+        assert!(!filter.is_excluded(Path::new("a/my-package.json")));
+    }
+
+    #[test]
+    fn exclude_patterns() {
+        let filter = PathFilter::new(&[], &["*.min.json", "test/fixtures"]).unwrap();
+
+        assert!(filter.is_excluded(Path::new("a/b.min.json")));
+        assert!(filter.is_excluded(Path::new("test/fixtures/a.json")));
+        assert!(filter.is_excluded(Path::new("./test/fixtures/a/b.json")));
+        assert!(!filter.is_excluded(Path::new("a/test/fixtures/a.json")));
+        assert!(!filter.is_excluded(Path::new("a/b.json")));
+    }
+
+    #[test]
+    fn is_json() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let write = |name: &str, contents: &str| {
+            let path = tempdir.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            path
+        };
+        let filter = PathFilter::default().with_extensions(&[".json5"]);
+
+        assert!(filter.is_json(&write("a.JSON", "not json")));
+        assert!(filter.is_json(&write("a.json5", "not json")));
+        assert!(filter.is_json(&write(".babelrc", "not json")));
+        assert!(filter.is_json(&write(".sneakyrc", "\u{FEFF}\n  {\"a\": 1}")));
+        assert!(filter.is_json(&write("list", "[1, 2]")));
+        assert!(!filter.is_json(&write("a.yml", "a: value")));
+        assert!(!filter.is_json(&write("empty", "")));
+        assert!(filter.is_json(&write("broken", "{\"a\": 1,}")));
+        assert!(filter.is_json_contents(Path::new("a.json"), b"not json"));
+        assert!(filter.is_json_contents(Path::new("list"), b" [1]"));
+        assert!(!filter.is_json_contents(Path::new("a.yml"), b"a: value"));
+        assert!(!filter.is_json_contents(Path::new("a.yml"), b""));
+    }
+
+    #[test]
+    fn glob_pattern() {
+        let pattern = GlobPattern::new(Path::new("config/**/*.json")).unwrap();
+        assert_eq!(pattern.base, PathBuf::from("config"));
+        assert_eq!(pattern.max_depth, None);
+        assert!(pattern.is_match(Path::new("config/a.json")));
+        assert!(pattern.is_match(Path::new("config/a/b.json")));
+        assert!(!pattern.is_match(Path::new("config/a/b.yml")));
+
+        let pattern = GlobPattern::new(Path::new("./a/[bc]?/*.json")).unwrap();
+        assert_eq!(pattern.base, PathBuf::from("./a"));
+        assert_eq!(pattern.max_depth, Some(2));
+        assert!(pattern.is_match(Path::new("./a/b1/x.json")));
+        assert!(!pattern.is_match(Path::new("./a/d1/x.json")));
+        assert!(!pattern.is_match(Path::new("./a/b1/c/x.json")));
+
+        let pattern = GlobPattern::new(Path::new("*.json")).unwrap();
+        assert_eq!(pattern.base, PathBuf::from(""));
+        assert!(pattern.is_match(Path::new("./x.json")));
+    }
+
+    #[test]
+    fn include_patterns() {
+        let filter =
+            PathFilter::new(&["config/**/*.json", "package.json"], &["config/skip"]).unwrap();
+
+        assert!(!filter.is_excluded(Path::new("config/a/b.json")));
+        assert!(!filter.is_excluded(Path::new("package.json")));
+        assert!(filter.is_excluded(Path::new("config/skip/b.json")));
+        assert!(filter.is_excluded(Path::new("other/b.json")));
+        assert!(!filter.is_excluded_dir(Path::new("node_modules")));
+    }
+}
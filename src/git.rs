@@ -0,0 +1,606 @@
+use git2::{
+    AttrCheckFlags, AttrValue, ConfigLevel, Delta, DiffFindOptions, DiffOptions, ErrorCode, Index,
+    Oid, Patch, Repository, Status, StatusEntry, StatusOptions,
+};
+use std::env;
+use std::fs;
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use crate::sort::{
+    skip_unparsed, sort_contents, FileOptions, JsonError, PathFilter, SortMode, SortResult,
+    NOT_JSON,
+};
+
+/// Commits listed in this file are skipped by `git blame`, when configured with `blame.ignoreRevsFile`
+const BLAME_IGNORE_REVS: &str = ".git-blame-ignore-revs";
+
+fn current_dir() -> PathBuf {
+    let dir = env::current_dir().unwrap_or(PathBuf::from("."));
+    dir.canonicalize().unwrap_or(dir)
+}
+
+/// Open the repository containing the current directory, which may be a linked worktree.
+///
+/// Uses the index at `GIT_INDEX_FILE` if set. Git points hooks at a temporary index
+/// for `git commit -a` and `git commit <paths>`, and keeps the default index locked.
+pub fn open_repo() -> Result<Repository, git2::Error> {
+    let repo = Repository::discover(current_dir())?;
+    if let Some(path) = env::var_os("GIT_INDEX_FILE") {
+        log::debug!("Using index file {:?}", path);
+        repo.set_index(&mut Index::open(Path::new(&path))?)?;
+    }
+
+    Ok(repo)
+}
+
+/// Root of the work tree of `repo`. Paths reported by git are relative to this directory
+fn workdir(repo: &Repository) -> Result<PathBuf, git2::Error> {
+    match repo.workdir() {
+        Some(dir) => Ok(dir.canonicalize().unwrap_or(dir.to_path_buf())),
+        None => Err(git2::Error::from_str(
+            "this operation must be run in a work tree",
+        )),
+    }
+}
+
+/// Absolute `path` relative to the current directory, or unchanged if it is outside the current directory
+fn relative_to(cwd: &Path, path: PathBuf) -> PathBuf {
+    match path.strip_prefix(cwd) {
+        Ok(p) => p.to_path_buf(),
+        Err(_) => path,
+    }
+}
+
+/// Configure a git driver for JSON files in the repository containing the current directory.
+/// Sets `key` to `command` in the local git config, and adds `attribute` for `*.json` files
+/// to the `.gitattributes` file in the root of the work tree if it does not already contain it.
+///
+/// Returns the path of the `.gitattributes` file.
+pub fn install_driver(key: &str, command: &str, attribute: &str) -> Result<PathBuf, git2::Error> {
+    let repo = open_repo()?;
+    let workdir = workdir(&repo)?;
+
+    let mut config = repo.config()?.open_level(ConfigLevel::Local)?;
+    config.set_str(key, command)?;
+
+    let attributes = workdir.join(".gitattributes");
+    let pattern = format!("*.json {attribute}");
+    let mut contents = match fs::read_to_string(&attributes) {
+        Ok(s) => s,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(git2::Error::from_str(&e.to_string())),
+    };
+    if !contents.lines().any(|l| l.trim() == pattern) {
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents += &format!("{pattern}\n");
+        fs::write(&attributes, contents).map_err(|e| git2::Error::from_str(&e.to_string()))?;
+    }
+
+    Ok(attributes)
+}
+
+/// Settings for a file from `.gitattributes`
+///
+///  * `skip` - why the file should not be sorted, if the `roast` attribute is false or the file is `linguist-generated`
+///  * `arrays` - value of the `roast-arrays` attribute
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathAttributes {
+    pub skip: Option<String>,
+    pub arrays: Option<bool>,
+}
+
+/// Reads attributes from the repository containing the current directory
+pub struct Attributes {
+    repo: Repository,
+    workdir: PathBuf,
+}
+
+impl Attributes {
+    /// None if the current directory is not in a git work tree
+    pub fn open() -> Option<Self> {
+        let repo = open_repo().ok()?;
+        let workdir = workdir(&repo).ok()?;
+        Some(Attributes { repo, workdir })
+    }
+
+    fn get_bool(&self, path: &Path, name: &str) -> Option<bool> {
+        let value = self
+            .repo
+            .get_attr(path, name, AttrCheckFlags::FILE_THEN_INDEX)
+            .ok()?;
+        match AttrValue::from_string(value) {
+            AttrValue::True | AttrValue::String("true") => Some(true),
+            AttrValue::False | AttrValue::String("false") => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Attributes of the file at absolute `path`. Files outside the work tree have no attributes.
+    pub fn lookup(&self, path: &Path) -> PathAttributes {
+        let Ok(relative) = path.strip_prefix(&self.workdir) else {
+            return PathAttributes::default();
+        };
+
+        let skip = match self.get_bool(relative, "roast") {
+            Some(true) => None,
+            Some(false) => Some("roast=false".to_string()),
+            None => (self.get_bool(relative, "linguist-generated") == Some(true))
+                .then(|| "linguist-generated".to_string()),
+        };
+
+        PathAttributes {
+            skip,
+            arrays: self.get_bool(relative, "roast-arrays"),
+        }
+    }
+}
+
+/// Lines of the file at `path` in the work tree that changed since HEAD, as 1 based line ranges.
+/// A line removed from between two lines is reported as a change to both of them.
+///
+/// None if the file is not in HEAD, so every line is new.
+pub fn changed_lines(path: &Path) -> Result<Option<Vec<RangeInclusive<usize>>>, git2::Error> {
+    let repo = open_repo()?;
+    let workdir = workdir(&repo)?;
+    let full = path.canonicalize().unwrap_or(path.to_path_buf());
+    let Ok(relative) = full.strip_prefix(&workdir) else {
+        return Ok(None);
+    };
+
+    let head = repo.head()?.peel_to_tree()?;
+    let old = match head.get_path(relative) {
+        Ok(entry) => repo.find_blob(entry.id())?,
+        Err(_) => return Ok(None),
+    };
+    let new = fs::read(&full).map_err(|e| git2::Error::from_str(&e.to_string()))?;
+
+    let mut opts = DiffOptions::new();
+    opts.context_lines(0);
+    let patch = Patch::from_buffers(
+        old.content(),
+        Some(relative),
+        &new,
+        Some(relative),
+        Some(&mut opts),
+    )?;
+
+    let mut lines = vec![];
+    for i in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(i)?;
+        let start = hunk.new_start() as usize;
+        let range = match hunk.new_lines() as usize {
+            // removed lines are between new_start and the next line
+            0 => start.max(1)..=start + 1,
+            count => start..=start + count - 1,
+        };
+        lines.push(range);
+    }
+
+    Ok(Some(lines))
+}
+
+/// Which files to select from a git repository
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GitSelection {
+    /// Tracked files with unstaged modifications in the work tree
+    Modified,
+    /// Every tracked file
+    Tracked,
+    /// Files added, modified or renamed in HEAD since it diverged from a commit
+    Since(String),
+}
+
+/// Files selected from the git repository containing the current directory, sorted by path.
+///
+///  * `untracked` - also select untracked files that are not ignored
+///  * `submodules` - also select files from the same selection in each checked out submodule
+///
+/// Paths are relative to the current directory, or absolute if they are outside of it.
+pub fn select_files(
+    selection: &GitSelection,
+    untracked: bool,
+    submodules: bool,
+) -> Result<Vec<PathBuf>, git2::Error> {
+    let repo = open_repo()?;
+    let cwd = current_dir();
+    let mut files: Vec<PathBuf> = select_repo_files(&repo, selection, untracked, submodules)?
+        .into_iter()
+        .map(|f| relative_to(&cwd, f))
+        .collect();
+    files.sort();
+    files.dedup();
+
+    Ok(files)
+}
+
+/// Absolute paths of the files selected from `repo`, and from its submodules if `submodules` is set
+fn select_repo_files(
+    repo: &Repository,
+    selection: &GitSelection,
+    untracked: bool,
+    submodules: bool,
+) -> Result<Vec<PathBuf>, git2::Error> {
+    let workdir = workdir(repo)?;
+
+    let mut relative = match selection {
+        GitSelection::Modified => modified_files(repo)?,
+        GitSelection::Tracked => tracked_files(repo)?,
+        GitSelection::Since(rev) => changed_since(repo, rev)?,
+    };
+    if untracked {
+        relative.extend(untracked_files(repo)?);
+    }
+
+    // deleted files are still tracked until the deletion is committed, and submodules are directories
+    let mut files: Vec<PathBuf> = relative
+        .iter()
+        .map(|f| workdir.join(f))
+        .filter(|f| {
+            let exists = f.is_file();
+            if !exists {
+                log::debug!("Ignored, not a file in the work tree: {:?}", f.to_str());
+            }
+            exists
+        })
+        .collect();
+
+    if submodules {
+        for submodule in repo.submodules()? {
+            let sub_repo = match submodule.open() {
+                Ok(r) => r,
+                Err(e) => {
+                    log::debug!("Ignored submodule {:?}: {e}", submodule.path().to_str());
+                    continue;
+                }
+            };
+            let sub_selection = match selection {
+                GitSelection::Since(rev) => submodule_since(repo, rev, submodule.path())?,
+                other => other.clone(),
+            };
+            log::debug!(
+                "Reading paths from submodule {:?}",
+                submodule.path().to_str()
+            );
+            files.extend(select_repo_files(
+                &sub_repo,
+                &sub_selection,
+                untracked,
+                submodules,
+            )?);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Selection of files changed in a submodule, since the commit it was at in `rev` of its parent repository
+fn submodule_since(
+    parent: &Repository,
+    rev: &str,
+    path: &Path,
+) -> Result<GitSelection, git2::Error> {
+    let tree = parent.revparse_single(rev)?.peel_to_tree()?;
+    match tree.get_path(path) {
+        Ok(entry) => Ok(GitSelection::Since(entry.id().to_string())),
+        // every file in a submodule added since `rev` is new
+        Err(_) => Ok(GitSelection::Tracked),
+    }
+}
+
+fn modified_files(repo: &Repository) -> Result<Vec<PathBuf>, git2::Error> {
+    let statuses = repo.statuses(None)?;
+
+    let res = statuses
+        .iter()
+        .filter(|se| {
+            let s: Status = se.status();
+            // index = staged, wt + not new = tracked, unstaged
+            s.is_wt_modified() || s.is_wt_renamed() || s.is_wt_typechange()
+        })
+        .filter_map(|s| s.path().map(PathBuf::from))
+        .collect();
+
+    Ok(res)
+}
+
+fn tracked_files(repo: &Repository) -> Result<Vec<PathBuf>, git2::Error> {
+    let index = repo.index()?;
+
+    let res = index
+        .iter()
+        .filter_map(|e| std::str::from_utf8(&e.path).ok().map(PathBuf::from))
+        .collect();
+
+    Ok(res)
+}
+
+/// Files changed on the current branch since it diverged from `rev`, like `git diff rev...HEAD`
+fn changed_since(repo: &Repository, rev: &str) -> Result<Vec<PathBuf>, git2::Error> {
+    let head = repo.head()?.peel_to_commit()?;
+    let since = repo.revparse_single(rev)?.peel_to_commit()?;
+    // files changed only on `rev` are not part of this branch
+    let base = repo.merge_base(since.id(), head.id())?;
+    let old_tree = repo.find_commit(base)?.tree()?;
+    let new_tree = head.tree()?;
+
+    let mut diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+
+    let res = diff
+        .deltas()
+        .filter(|d| {
+            matches!(
+                d.status(),
+                Delta::Added | Delta::Modified | Delta::Renamed | Delta::Copied | Delta::Typechange
+            )
+        })
+        .filter_map(|d| d.new_file().path().map(PathBuf::from))
+        .collect();
+
+    Ok(res)
+}
+
+fn untracked_files(repo: &Repository) -> Result<Vec<PathBuf>, git2::Error> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+
+    let res = repo
+        .statuses(Some(&mut opts))?
+        .iter()
+        .filter(|se| se.status().is_wt_new())
+        .filter_map(|s| s.path().map(PathBuf::from))
+        .collect();
+
+    Ok(res)
+}
+
+/// Path of a staged file, relative to the root of the work tree
+fn staged_path(entry: &StatusEntry) -> Option<PathBuf> {
+    let s = entry.status();
+    if !(s.is_index_new() || s.is_index_modified() || s.is_index_renamed()) {
+        return None;
+    }
+
+    entry
+        .head_to_index()
+        .and_then(|d| d.new_file().path().map(PathBuf::from))
+        .or_else(|| entry.path().map(PathBuf::from))
+}
+
+/// Sort the staged contents of files in the git index, rather than the files in the work tree.
+///
+/// In [SortMode::Write] the sorted contents are written back to the index.
+/// Files in the work tree are only updated when they have no unstaged changes,
+/// so any unstaged edits to a partially staged file are kept as they are.
+pub fn sort_staged<F>(
+    filter: &PathFilter,
+    mode: SortMode,
+    mut options_for: F,
+) -> Result<Vec<SortResult>, git2::Error>
+where
+    F: FnMut(&Path) -> Result<FileOptions, JsonError>,
+{
+    let repo = open_repo()?;
+    let workdir = workdir(&repo)?;
+    let cwd = current_dir();
+
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false).renames_head_to_index(true);
+    let staged: Vec<PathBuf> = repo
+        .statuses(Some(&mut opts))?
+        .iter()
+        .filter_map(|se| staged_path(&se))
+        .collect();
+
+    let mut index = repo.index()?;
+    let mut results = vec![];
+
+    for relative in staged {
+        let path = relative_to(&cwd, workdir.join(&relative));
+        if filter.is_excluded(&path) {
+            log::debug!("Ignored: {:?}", relative.to_str());
+            continue;
+        }
+
+        // conflicted files have no stage 0 entry
+        let Some(entry) = index.get_path(&relative, 0) else {
+            log::debug!("Not in index: {:?}", relative.to_str());
+            continue;
+        };
+        let blob = repo.find_blob(entry.id)?;
+        let sniffed = !filter.has_json_name(&relative);
+        if !filter.is_json_contents(&relative, blob.content()) {
+            log::debug!("Skipped, not JSON: {:?}", relative.to_str());
+            results.push(SortResult::skipped(&path, NOT_JSON));
+            continue;
+        }
+
+        let contents = match std::str::from_utf8(blob.content()) {
+            Ok(s) => s.to_string(),
+            Err(error) => {
+                log::debug!("Staged file is not valid UTF-8: {error}");
+                results.push(SortResult::new(&path, Some(JsonError::ReadError)));
+                continue;
+            }
+        };
+
+        let result = match options_for(&path) {
+            Ok(FileOptions::Sort(options)) => {
+                let result = sort_contents(&path, &contents, mode, &options, |sorted| {
+                    if sorted == contents {
+                        return Ok(());
+                    }
+                    write_staged(&repo, &mut index, entry, &path, &contents, sorted).map_err(|e| {
+                        log::debug!("Failed to update index: {e}");
+                        JsonError::WriteError
+                    })
+                });
+                skip_unparsed(result, sniffed)
+            }
+            Ok(FileOptions::Skip(reason)) => {
+                log::debug!("Skipped, {reason}: {:?}", path.to_str());
+                SortResult::skipped(&path, &reason)
+            }
+            Err(error) => SortResult::new(&path, Some(error)),
+        };
+        results.push(result);
+    }
+
+    if mode == SortMode::Write {
+        index.write()?;
+    }
+
+    Ok(results)
+}
+
+/// Replace the staged contents of a file with `sorted`.
+/// The work tree file is also replaced, unless it has unstaged changes.
+fn write_staged(
+    repo: &Repository,
+    index: &mut git2::Index,
+    mut entry: git2::IndexEntry,
+    path: &Path,
+    staged: &str,
+    sorted: String,
+) -> Result<(), git2::Error> {
+    entry.id = repo.blob(sorted.as_bytes())?;
+    entry.file_size = sorted.len() as u32;
+    index.add(&entry)?;
+
+    match fs::read(path) {
+        Ok(worktree) if worktree == staged.as_bytes() => {
+            fs::write(path, sorted).map_err(|e| git2::Error::from_str(&e.to_string()))?;
+        }
+        _ => log::debug!("Keeping unstaged changes: {:?}", path.to_str()),
+    }
+
+    Ok(())
+}
+
+/// Fail if the index of the repository containing the current directory has any staged changes,
+/// as they would be included in a commit made by [commit_sorted]
+pub fn ensure_nothing_staged() -> Result<(), git2::Error> {
+    let repo = open_repo()?;
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false);
+    let staged = Status::INDEX_NEW
+        | Status::INDEX_MODIFIED
+        | Status::INDEX_DELETED
+        | Status::INDEX_RENAMED
+        | Status::INDEX_TYPECHANGE;
+    let statuses = repo.statuses(Some(&mut opts))?;
+    if statuses.iter().any(|se| se.status().intersects(staged)) {
+        return Err(git2::Error::from_str(
+            "the index contains staged changes, commit or unstage them before using --commit",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fail if any of the files at `paths` differ from the index in the work tree. Committing them would
+/// include those changes in a commit made by [commit_sorted], hiding them from `git blame`.
+pub fn ensure_unmodified(paths: &[PathBuf]) -> Result<(), git2::Error> {
+    let repo = open_repo()?;
+    let workdir = workdir(&repo)?;
+    let modified = Status::WT_NEW
+        | Status::WT_MODIFIED
+        | Status::WT_DELETED
+        | Status::WT_RENAMED
+        | Status::WT_TYPECHANGE;
+
+    for path in paths {
+        let relative = in_workdir(&workdir, path)?;
+        if repo.status_file(&relative)?.intersects(modified) {
+            return Err(git2::Error::from_str(&format!(
+                "{} has uncommitted changes, commit or stash them before using --commit",
+                relative.display()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Path of the file at `path` relative to the root of the work tree `workdir`
+fn in_workdir(workdir: &Path, path: &Path) -> Result<PathBuf, git2::Error> {
+    let full = path.canonicalize().unwrap_or(path.to_path_buf());
+    match full.strip_prefix(workdir) {
+        Ok(relative) => Ok(relative.to_path_buf()),
+        Err(_) => Err(git2::Error::from_str(&format!(
+            "{} is outside the repository",
+            path.display()
+        ))),
+    }
+}
+
+/// Commit the sorted files at `paths` with `message`,
+/// then append the id of that commit to `.git-blame-ignore-revs` in a second commit.
+///
+/// Returns the id of the commit containing the sorted files.
+pub fn commit_sorted(paths: &[PathBuf], message: &str) -> Result<Oid, git2::Error> {
+    let repo = open_repo()?;
+    let workdir = workdir(&repo)?;
+    let mut index = repo.index()?;
+
+    for path in paths {
+        index.add_path(&in_workdir(&workdir, path)?)?;
+    }
+    let sort_commit = commit_index(&repo, &mut index, message)?;
+
+    let ignore_revs = workdir.join(BLAME_IGNORE_REVS);
+    let mut revs = match fs::read_to_string(&ignore_revs) {
+        Ok(s) => s,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(git2::Error::from_str(&e.to_string())),
+    };
+    if !revs.is_empty() && !revs.ends_with('\n') {
+        revs.push('\n');
+    }
+    let summary = message.lines().next().unwrap_or_default();
+    revs.push_str(&format!("# {summary}\n{sort_commit}\n"));
+    fs::write(&ignore_revs, revs).map_err(|e| git2::Error::from_str(&e.to_string()))?;
+
+    index.add_path(Path::new(BLAME_IGNORE_REVS))?;
+    let short_id = &sort_commit.to_string()[..7];
+    commit_index(
+        &repo,
+        &mut index,
+        &format!("Add {short_id} to {BLAME_IGNORE_REVS}"),
+    )?;
+
+    Ok(sort_commit)
+}
+
+/// Write `index` and commit it on top of HEAD, which may be unborn
+fn commit_index(
+    repo: &Repository,
+    index: &mut git2::Index,
+    message: &str,
+) -> Result<Oid, git2::Error> {
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = repo.signature()?;
+    let parent = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit()?),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+        Err(e) => return Err(e),
+    };
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+}
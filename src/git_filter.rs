@@ -0,0 +1,310 @@
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::git::install_driver;
+use crate::sort::{sort_json_string, FileOptions, JsonError, PathFilter};
+
+/// Name of the filter driver in git config and `.gitattributes`
+const DRIVER_NAME: &str = "roast";
+
+/// Largest amount of data git allows in a single pkt-line, excluding the 4 byte length header
+const MAX_PKT_DATA: usize = 65516;
+
+/// Reads pkt-lines, see [gitprotocol-common](https://git-scm.com/docs/gitprotocol-common)
+struct PktReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> PktReader<R> {
+    /// Read the data of one pkt-line, or None for a flush packet
+    fn read_pkt(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut header = [0u8; 4];
+        self.reader.read_exact(&mut header)?;
+
+        let len = std::str::from_utf8(&header)
+            .ok()
+            .and_then(|h| usize::from_str_radix(h, 16).ok())
+            .ok_or_else(|| invalid_data(format!("invalid pkt-line length {header:?}")))?;
+        match len {
+            0 => Ok(None),
+            1..=4 => Err(invalid_data(format!("invalid pkt-line length {len}"))),
+            _ => {
+                let mut data = vec![0u8; len - 4];
+                self.reader.read_exact(&mut data)?;
+                Ok(Some(data))
+            }
+        }
+    }
+
+    /// Read text lines up to the next flush packet
+    fn read_lines(&mut self) -> io::Result<Vec<String>> {
+        let mut lines = vec![];
+        while let Some(data) = self.read_pkt()? {
+            let line = String::from_utf8_lossy(&data);
+            lines.push(line.trim_end_matches('\n').to_string());
+        }
+
+        Ok(lines)
+    }
+
+    /// Read binary content up to the next flush packet
+    fn read_content(&mut self) -> io::Result<Vec<u8>> {
+        let mut content = vec![];
+        while let Some(data) = self.read_pkt()? {
+            content.extend(data);
+        }
+
+        Ok(content)
+    }
+}
+
+/// Writes pkt-lines, see [PktReader]
+struct PktWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PktWriter<W> {
+    fn write_pkt(&mut self, data: &[u8]) -> io::Result<()> {
+        write!(self.writer, "{:04x}", data.len() + 4)?;
+        self.writer.write_all(data)
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        self.write_pkt(format!("{line}\n").as_bytes())
+    }
+
+    fn write_content(&mut self, content: &[u8]) -> io::Result<()> {
+        for chunk in content.chunks(MAX_PKT_DATA) {
+            self.write_pkt(chunk)?;
+        }
+        Ok(())
+    }
+
+    fn flush_pkt(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"0000")?;
+        self.writer.flush()
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Value of a `key=value` line
+fn value<'a>(lines: &'a [String], key: &str) -> Option<&'a str> {
+    lines
+        .iter()
+        .find_map(|l| l.strip_prefix(key).and_then(|l| l.strip_prefix('=')))
+}
+
+/// Sort the contents of a file being cleaned by git. Files that do not look like JSON, or are skipped, are not changed.
+fn clean<F>(
+    path: &Path,
+    content: Vec<u8>,
+    filter: &PathFilter,
+    options_for: &mut F,
+) -> Result<Vec<u8>, JsonError>
+where
+    F: FnMut(&Path) -> Result<FileOptions, JsonError>,
+{
+    if !filter.is_json_contents(path, &content) {
+        log::debug!("Skipped, not JSON: {:?}", path.to_str());
+        return Ok(content);
+    }
+
+    let options = match options_for(path)? {
+        FileOptions::Sort(options) => options,
+        FileOptions::Skip(reason) => {
+            log::debug!("Skipped, {reason}: {:?}", path.to_str());
+            return Ok(content);
+        }
+    };
+
+    let input = String::from_utf8(content).map_err(|_| JsonError::ReadError)?;
+    match sort_json_string(&input, &options) {
+        // only starts like JSON, e.g. a TOML file starting with a `[table]`
+        Err(JsonError::ParseError(_)) if !filter.has_json_name(path) => {
+            log::debug!("Skipped, not JSON: {:?}", path.to_str());
+            Ok(input.into_bytes())
+        }
+        result => result.map(String::into_bytes),
+    }
+}
+
+/// Run as a git long-running filter process, sorting JSON files as they are cleaned,
+/// see [gitattributes](https://git-scm.com/docs/gitattributes#_long_running_filter_process).
+///
+/// Reads requests from `input` and writes responses to `output`, until git closes `input`.
+pub fn process<R, W, F>(
+    input: R,
+    output: W,
+    filter: &PathFilter,
+    mut options_for: F,
+) -> io::Result<()>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(&Path) -> Result<FileOptions, JsonError>,
+{
+    let mut reader = PktReader { reader: input };
+    let mut writer = PktWriter { writer: output };
+
+    // handshake
+    let welcome = reader.read_lines()?;
+    if welcome.first().map(String::as_str) != Some("git-filter-client")
+        || !welcome.iter().any(|l| l == "version=2")
+    {
+        return Err(invalid_data(format!(
+            "unsupported filter protocol {welcome:?}"
+        )));
+    }
+    writer.write_line("git-filter-server")?;
+    writer.write_line("version=2")?;
+    writer.flush_pkt()?;
+
+    let capabilities = reader.read_lines()?;
+    if !capabilities.iter().any(|c| c == "capability=clean") {
+        return Err(invalid_data(
+            "git does not support the clean capability".into(),
+        ));
+    }
+    writer.write_line("capability=clean")?;
+    writer.flush_pkt()?;
+
+    loop {
+        let request = match reader.read_lines() {
+            Ok(r) => r,
+            // git closes stdin when it has no more files to filter
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let content = reader.read_content()?;
+        let path = PathBuf::from(value(&request, "pathname").unwrap_or_default());
+
+        let result = match value(&request, "command") {
+            Some("clean") => clean(&path, content, filter, &mut options_for),
+            command => {
+                log::debug!("Unsupported filter command {command:?}");
+                Err(JsonError::ReadError)
+            }
+        };
+
+        match result {
+            Ok(sorted) => {
+                writer.write_line("status=success")?;
+                writer.flush_pkt()?;
+                writer.write_content(&sorted)?;
+                writer.flush_pkt()?;
+                // keep the status sent before the content
+                writer.flush_pkt()?;
+            }
+            Err(e) => {
+                log::error!("roast: could not sort {}: {e}", path.display());
+                writer.write_line("status=error")?;
+                writer.flush_pkt()?;
+            }
+        }
+    }
+}
+
+/// Configure the repository to clean JSON files with roast.
+///
+/// Adds the filter process command to the local git config, and a `*.json` pattern to the `.gitattributes` file
+/// in the root of the work tree if it does not already contain one. Returns the path of the `.gitattributes` file.
+pub fn install() -> Result<PathBuf, git2::Error> {
+    install_driver(
+        &format!("filter.{DRIVER_NAME}.process"),
+        "roast filter process",
+        &format!("filter={DRIVER_NAME}"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pkt(data: &str) -> String {
+        format!("{:04x}{data}", data.len() + 4)
+    }
+
+    #[test]
+    fn read_and_write_pkt_lines() {
+        let input = format!("{}{}0000", pkt("command=clean\n"), pkt("pathname=a.json\n"));
+        let mut reader = PktReader {
+            reader: input.as_bytes(),
+        };
+        assert_eq!(
+            reader.read_lines().unwrap(),
+            vec!["command=clean", "pathname=a.json"]
+        );
+
+        let mut writer = PktWriter { writer: vec![] };
+        writer.write_line("status=success").unwrap();
+        writer.flush_pkt().unwrap();
+        assert_eq!(
+            String::from_utf8(writer.writer).unwrap(),
+            "0013status=success\n0000"
+        );
+    }
+
+    #[test]
+    fn long_content_is_split() {
+        let content = vec![b'a'; MAX_PKT_DATA + 1];
+        let mut writer = PktWriter { writer: vec![] };
+        writer.write_content(&content).unwrap();
+        writer.flush_pkt().unwrap();
+
+        let mut reader = PktReader {
+            reader: writer.writer.as_slice(),
+        };
+        assert_eq!(reader.read_pkt().unwrap().unwrap().len(), MAX_PKT_DATA);
+        assert_eq!(reader.read_pkt().unwrap().unwrap().len(), 1);
+        assert_eq!(reader.read_pkt().unwrap(), None);
+    }
+
+    #[test]
+    fn clean_request() {
+        let options = crate::sort::SortOptions {
+            line_ending: crate::lines::LineEnding::Lf,
+            ..Default::default()
+        };
+        let input = [
+            pkt("git-filter-client\n"),
+            pkt("version=2\n"),
+            "0000".into(),
+            pkt("capability=clean\n"),
+            pkt("capability=smudge\n"),
+            "0000".into(),
+            pkt("command=clean\n"),
+            pkt("pathname=a.json\n"),
+            "0000".into(),
+            pkt("{\"b\": 1, \"a\": 0}"),
+            "0000".into(),
+        ]
+        .concat();
+
+        let mut output = vec![];
+        process(
+            input.as_bytes(),
+            &mut output,
+            &PathFilter::default(),
+            |_| Ok(FileOptions::Sort(options.clone())),
+        )
+        .unwrap();
+
+        let expected = [
+            pkt("git-filter-server\n"),
+            pkt("version=2\n"),
+            "0000".into(),
+            pkt("capability=clean\n"),
+            "0000".into(),
+            pkt("status=success\n"),
+            "0000".into(),
+            pkt("{\n\t\"a\": 0,\n\t\"b\": 1\n}\n"),
+            "0000".into(),
+            "0000".into(),
+        ]
+        .concat();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::git::open_repo;
+
+const HOOK_NAME: &str = "pre-commit";
+
+/// Name an existing hook is moved to, when roast is chained after it
+const CHAINED_HOOK_NAME: &str = "pre-commit.chained";
+
+/// Marks a hook as installed by roast, so it can be updated or uninstalled
+const HOOK_MARKER: &str = "# roast pre-commit hook";
+
+/// What the pre-commit hook does with staged JSON files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookMode {
+    /// Abort the commit if any staged file is not sorted
+    Check,
+    /// Sort staged files before they are committed
+    Fix,
+}
+
+/// Reason why a hook could not be installed or uninstalled
+#[derive(Debug)]
+pub enum HookError {
+    Git(git2::Error),
+    Io(io::Error),
+    /// A hook that was not installed by roast exists at this path
+    ForeignHook(PathBuf),
+    /// The hook to uninstall at this path was not installed by roast
+    NotRoastHook(PathBuf),
+    /// There is no roast hook at this path
+    NotInstalled(PathBuf),
+}
+
+impl Display for HookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookError::Git(e) => write!(f, "{}", e.message()),
+            HookError::Io(e) => write!(f, "{e}"),
+            HookError::ForeignHook(p) => write!(
+                f,
+                "a pre-commit hook not installed by roast already exists at {}. Use --chain to run it before roast",
+                p.display()
+            ),
+            HookError::NotRoastHook(p) => write!(
+                f,
+                "the pre-commit hook at {} was not installed by roast, remove it yourself if it is no longer needed",
+                p.display()
+            ),
+            HookError::NotInstalled(p) => write!(f, "no roast pre-commit hook found at {}", p.display()),
+        }
+    }
+}
+
+impl From<git2::Error> for HookError {
+    fn from(e: git2::Error) -> Self {
+        HookError::Git(e)
+    }
+}
+
+impl From<io::Error> for HookError {
+    fn from(e: io::Error) -> Self {
+        HookError::Io(e)
+    }
+}
+
+/// Directory git runs hooks from, `core.hooksPath` if set
+fn hooks_dir() -> Result<PathBuf, HookError> {
+    let repo = open_repo()?;
+
+    // relative hook paths are relative to the root of the work tree
+    let base = repo.workdir().unwrap_or(repo.path()).to_path_buf();
+    let dir = match repo.config()?.get_path("core.hooksPath") {
+        Ok(path) => base.join(path),
+        Err(_) => repo.commondir().join("hooks"),
+    };
+
+    Ok(dir)
+}
+
+fn is_roast_hook(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|s| s.contains(HOOK_MARKER))
+}
+
+fn hook_script(mode: HookMode, chained: bool) -> String {
+    let mut script = format!("#!/bin/sh\n{HOOK_MARKER}\n# Remove with `roast hook uninstall`\n\n");
+    if chained {
+        script += &format!("\"$(dirname \"$0\")/{CHAINED_HOOK_NAME}\" \"$@\" || exit $?\n");
+    }
+    script += match mode {
+        HookMode::Check => "exec roast --staged --check\n",
+        HookMode::Fix => "exec roast --staged\n",
+    };
+
+    script
+}
+
+/// Write a pre-commit hook that runs roast on staged JSON files, returning the path of the hook.
+///
+/// An existing hook installed by roast is replaced. Any other existing hook is only kept if `chain` is set,
+/// in which case it is renamed and run before roast.
+pub fn install(mode: HookMode, chain: bool) -> Result<PathBuf, HookError> {
+    let dir = hooks_dir()?;
+    let hook = dir.join(HOOK_NAME);
+    let chained = dir.join(CHAINED_HOOK_NAME);
+
+    if hook.exists() && !is_roast_hook(&hook) {
+        if !chain {
+            return Err(HookError::ForeignHook(hook));
+        }
+        if chained.exists() {
+            let message = format!("{} already exists", chained.display());
+            return Err(HookError::Io(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                message,
+            )));
+        }
+        log::debug!("Moving existing hook to {:?}", chained.to_str());
+        fs::rename(&hook, &chained)?;
+    }
+
+    fs::create_dir_all(&dir)?;
+    fs::write(&hook, hook_script(mode, chained.exists()))?;
+    set_executable(&hook)?;
+
+    Ok(hook)
+}
+
+/// Remove the pre-commit hook installed by roast, restoring any hook it was chained after.
+/// Returns the path of the removed hook.
+pub fn uninstall() -> Result<PathBuf, HookError> {
+    let dir = hooks_dir()?;
+    let hook = dir.join(HOOK_NAME);
+    let chained = dir.join(CHAINED_HOOK_NAME);
+
+    if !hook.exists() {
+        return Err(HookError::NotInstalled(hook));
+    }
+    if !is_roast_hook(&hook) {
+        return Err(HookError::NotRoastHook(hook));
+    }
+
+    fs::remove_file(&hook)?;
+    if chained.exists() {
+        log::debug!("Restoring chained hook {:?}", chained.to_str());
+        fs::rename(&chained, &hook)?;
+    }
+
+    Ok(hook)
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
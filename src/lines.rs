@@ -40,6 +40,16 @@ impl LineEnding {
         }
     }
 
+    /// Name of the line ending, as accepted by [LineEnding::from_str]
+    pub fn name(&self) -> &str {
+        match self {
+            LineEnding::Cr => "cr",
+            LineEnding::Lf => "lf",
+            LineEnding::CrLf => "crlf",
+            LineEnding::SystemDefault => "original",
+        }
+    }
+
     pub fn parse_str(s: &str) -> LineEnding {
         if s.contains(LineEnding::CrLf.as_str()) {
             LineEnding::CrLf
@@ -1,20 +1,40 @@
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use colored::*;
-use git2::{Repository, Status};
 use log::{Level, LevelFilter, Metadata, Record};
 use sort::sort_json_string;
+use std::collections::BTreeMap;
 use std::fmt::Display;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::exit;
-use std::{env, io};
+use std::{fs, io};
 
+mod collate;
+mod config;
+mod diff;
+mod filters;
 mod formatter;
+mod git;
+mod git_filter;
+mod hook;
 mod lines;
+mod merge;
+mod order;
+mod parse_error;
+mod partial;
 mod sort;
-
+mod textconv;
+
+use crate::collate::CollationMode;
+use crate::config::{PartialOptions, Resolved, Settings};
+use crate::diff::{colorize, unified_diff};
+use crate::filters::{is_glob, GlobPattern};
+use crate::git::GitSelection;
+use crate::hook::{HookError, HookMode};
 use crate::lines::LineEnding;
-use crate::sort::{sort_files, SortResult};
+use crate::order::{KeyOrder, KeyPattern, PinRule};
+use crate::sort::{sort_files, FileOptions, JsonError, PathFilter, SortMode, SortResult, NOT_JSON};
 
 const APP_NAME: &str = "roast";
 const APP_VERSION: &str = "0.3.0";
@@ -22,34 +42,211 @@ const APP_AUTHOR: &str = "Nicholas Kress";
 const APP_ABOUT: &str = "Sort JSON files by key.
 Rust implementation of the npm package: json-sort-cli.";
 
-const INDENT_SIZE_SPACE: usize = 2;
-const INDENT_SIZE_TAB: usize = 1;
+// Process exit codes, documented in README.md
+// clap already exits with 2 on invalid usage
+const EXIT_OK: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+const EXIT_UNSORTED: i32 = 3;
+const EXIT_NOT_FOUND: i32 = 4;
+const EXIT_READ_ERROR: i32 = 5;
+const EXIT_PARSE_ERROR: i32 = 6;
+const EXIT_WRITE_ERROR: i32 = 7;
+const EXIT_NO_INPUTS: i32 = 8;
+const EXIT_CONFIG_ERROR: i32 = 9;
+
+const DEFAULT_COMMIT_MESSAGE: &str = "Sort JSON files with roast";
 
 static LOGGER: SimpleLogger = SimpleLogger;
 
+/// Conditions that may be configured to cause a non-zero exit code
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum FailOn {
+    /// A file could not be parsed as JSON
+    Parse,
+    /// A file does not exist
+    Missing,
+    /// The inputs don't lead to any JSON files
+    Empty,
+    /// Only fail on read and write errors
+    None,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the effective settings used to sort a file
+    PrintConfig {
+        /// Path of the file to resolve settings for
+        file: PathBuf,
+    },
+    /// Sort JSON files as they are added to a git repository, using a git clean filter
+    Filter {
+        #[command(subcommand)]
+        action: FilterAction,
+    },
+    /// Three-way merge of JSON files, for use as a git merge driver.
+    /// Writes the sorted result to OURS, with conflict markers around any keys that could not be merged
+    MergeDriver {
+        /// Common ancestor of both versions, %O
+        base: PathBuf,
+        /// Current version, the merged result is written to this file, %A
+        ours: PathBuf,
+        /// Other branch's version, %B
+        theirs: PathBuf,
+        /// Path of the file being merged, used to resolve sorting options, %P
+        path: Option<PathBuf>,
+    },
+    /// Manage a git pre-commit hook that sorts staged JSON files
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Print a canonical sorted rendering of a JSON file, for use as a git textconv command.
+    /// Files that are not JSON are printed unchanged
+    Textconv {
+        /// Path of the file to convert
+        #[clap(required_unless_present = "install")]
+        file: Option<PathBuf>,
+
+        /// Configure the git repository to diff *.json files with roast textconv, in .gitattributes and the local git config
+        #[clap(long, conflicts_with = "file")]
+        install: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum FilterAction {
+    /// Run as a long-running git filter process. Started by git, reading requests from stdin
+    Process,
+    /// Configure the git repository to clean *.json files with roast, in .gitattributes and the local git config
+    Install,
+}
+
+#[derive(Debug, Subcommand)]
+enum HookAction {
+    /// Install the pre-commit hook, respecting core.hooksPath
+    Install {
+        /// Sort staged files in the hook, instead of aborting the commit if any are not sorted
+        #[clap(long)]
+        fix: bool,
+
+        /// Keep an existing pre-commit hook not installed by roast, and run it before roast
+        #[clap(long)]
+        chain: bool,
+    },
+    /// Remove the pre-commit hook, restoring any hook it was chained after
+    Uninstall,
+}
+
 #[derive(Debug, Parser)]
-#[command(name = APP_NAME, version = APP_VERSION, author = APP_AUTHOR, about = APP_ABOUT)]
+#[command(name = APP_NAME, version = APP_VERSION, author = APP_AUTHOR, about = APP_ABOUT, disable_help_subcommand = true)]
 struct Args {
     /// Also sort any arrays if they contain only string elements
     #[clap(long, short = 'a')]
     arrays: bool,
 
+    /// Do not modify any files, exit with a non-zero code if any file is not already sorted
+    #[clap(long, short = 'c', conflicts_with = "dry")]
+    check: bool,
+
+    /// Commit the files changed by sorting, then add that commit to .git-blame-ignore-revs in a second commit.
+    /// Fails if the git index already contains staged changes, or a file it would sort has uncommitted changes
+    #[clap(long, conflicts_with_all = ["check", "diff", "dry", "staged", "recurse_submodules"])]
+    commit: bool,
+
+    /// Message for the commit created by --commit
+    #[clap(long = "commitMessage", alias = "commit-message", value_name = "MESSAGE", requires = "commit", default_value = DEFAULT_COMMIT_MESSAGE)]
+    commit_message: String,
+
+    /// How to compare keys and strings in arrays.
+    /// By default keys are sorted in byte order, and strings in arrays ignoring case, keeping the input order of strings that only differ by case
+    #[clap(long, value_enum, value_name = "MODE")]
+    collation: Option<CollationMode>,
+
+    /// Do not modify any files, print a unified diff of the changes sorting would make
+    #[clap(long, conflicts_with = "dry")]
+    diff: bool,
+
     /// Only list all the files to be processed
     #[clap(long, short = 'd')]
     dry: bool,
 
+    /// Do not sort files matching this glob pattern. May be used multiple times
+    #[clap(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Comma separated list of extra file extensions to sort as JSON.
+    /// By default only .json files, well known JSON dotfiles and files starting with { or [ that parse as JSON are sorted
+    /// from directories and glob patterns. Files named as arguments are always sorted
+    #[clap(long, value_delimiter = ',', value_name = "EXT")]
+    ext: Vec<String>,
+
+    /// Comma separated list of conditions that cause a non-zero exit code.
+    /// Read and write errors always cause a non-zero exit code
+    #[clap(long = "failOn", value_enum, value_delimiter = ',', default_values_t = [FailOn::Parse, FailOn::Missing])]
+    fail_on: Vec<FailOn>,
+
+    /// Read the paths to sort from a file, one per line. Use - to read from stdin
+    #[clap(long = "filesFrom", alias = "files-from", value_name = "PATH", conflicts_with_all = ["git", "since", "tracked", "untracked"])]
+    files_from: Option<PathBuf>,
+
     /// Sort any JSON files tracked by git, that have a modified status. Will not modify any untracked, staged, or ignored files
     #[clap(long, short = 'g')]
     git: bool,
 
+    /// Sort arrays of objects by a member that identifies each object, the first of "id", "name" or "key"
+    /// that every object has with a unique string or number. Other arrays of objects are left alone
+    #[clap(long = "identityKeys", alias = "identity-keys")]
+    identity_keys: bool,
+
+    /// Only sort files matching this glob pattern, including files that are ignored by default. May be used multiple times
+    #[clap(long, value_name = "GLOB")]
+    include: Vec<String>,
+
     /// How many spaces/tabs to use (default: 2 -> spaces, 1 -> tabs)
     #[clap(long = "indentationCount", short = 'i', default_value = "0")]
     indents: usize,
 
     /// Set to "cr", "crlf" or "lf". Otherwise, the original line ending of the file is used
-    #[clap(long = "lineEnding", short = 'l', default_value = LineEnding::SystemDefault.as_str())]
+    #[clap(long = "lineEnding", short = 'l')]
     #[arg(value_parser = LineEnding::from_str)]
-    line_ending: LineEnding,
+    line_ending: Option<LineEnding>,
+
+    /// Sort keys and arrays in natural order, comparing numbers in keys and strings by their value, e.g. item2 before item10
+    #[clap(long)]
+    natural: bool,
+
+    /// Paths read by --filesFrom are separated by NUL characters instead of newlines
+    #[clap(long, short = '0', requires = "files_from")]
+    null: bool,
+
+    /// Move this key to the start of every object. A regex may be written between slashes, e.g. /^x-/.
+    /// May be used multiple times, keys are pinned in the order given
+    #[clap(long = "pinFirst", alias = "pin-first", value_name = "KEY", value_parser = KeyPattern::parse)]
+    pin_first: Vec<KeyPattern>,
+
+    /// Move this key to the end of every object. A regex may be written between slashes, e.g. /^x-/.
+    /// May be used multiple times, keys are pinned in the order given
+    #[clap(long = "pinLast", alias = "pin-last", value_name = "KEY", value_parser = KeyPattern::parse)]
+    pin_last: Vec<KeyPattern>,
+
+    /// Sort the staged contents of JSON files in the git index, and write them back to the index.
+    /// Unstaged changes in the work tree are kept. Submodules are not sorted
+    #[clap(long, conflicts_with_all = ["git", "files_from", "files", "since", "tracked", "untracked", "recurse_submodules"])]
+    staged: bool,
+
+    /// Only sort the objects and arrays containing lines changed since the last git commit.
+    /// Everything else keeps its original order and formatting
+    #[clap(long = "onlyChanged", conflicts_with = "staged")]
+    only_changed: bool,
+
+    /// Also select files from each checked out git submodule, when selecting files from git
+    #[clap(long = "recurseSubmodules", alias = "recurse-submodules")]
+    recurse_submodules: bool,
+
+    /// Sort any JSON files tracked by git, that were added, modified or renamed on the current branch since it diverged from this commit,
+    /// as in `git diff REF...HEAD`
+    #[clap(long, value_name = "REF", conflicts_with = "tracked")]
+    since: Option<String>,
 
     /// Suppress output
     #[clap(long)]
@@ -59,13 +256,80 @@ struct Args {
     #[clap(long, short = 's')]
     spaces: bool,
 
+    /// Sort every JSON file tracked by git
+    #[clap(long)]
+    tracked: bool,
+
+    /// Also sort any untracked JSON files that are not ignored by git
+    #[clap(long)]
+    untracked: bool,
+
     /// Enable verbose output for debugging
     #[clap(long, short = 'v')]
     verbose: bool,
 
-    /// Space separated list of file paths to sort. 
+    /// Space separated list of file paths or glob patterns to sort.
+    /// Patterns starting with ! exclude matching files.
     /// If no paths are supplied, roast will read JSON from stdin and write to stdout.
     files: Vec<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+impl Args {
+    /// Split file arguments into paths to sort, and negated `!` patterns to exclude
+    fn split_negations(&self) -> (Vec<PathBuf>, Vec<String>) {
+        let mut files = vec![];
+        let mut negations = vec![];
+        for f in self.files.iter() {
+            match f.to_str().and_then(|s| s.strip_prefix('!')) {
+                Some(pattern) if !f.exists() => negations.push(pattern.to_string()),
+                _ => files.push(f.clone()),
+            }
+        }
+
+        // only negations means everything else in the current directory
+        if files.is_empty() && !negations.is_empty() && self.files_from.is_none() {
+            files.push(PathBuf::from("."));
+        }
+
+        (files, negations)
+    }
+
+    /// Files to select from git, if any git selection flag is set
+    fn git_selection(&self) -> Option<GitSelection> {
+        if let Some(rev) = &self.since {
+            Some(GitSelection::Since(rev.clone()))
+        } else if self.tracked {
+            Some(GitSelection::Tracked)
+        } else if self.git || self.untracked {
+            Some(GitSelection::Modified)
+        } else {
+            None
+        }
+    }
+
+    /// Sorting options set on the command line, these take precedence over any config file
+    fn partial_options(&self) -> PartialOptions {
+        PartialOptions {
+            arrays: self.arrays.then_some(true),
+            indentation_count: (self.indents > 0).then_some(self.indents),
+            line_ending: self.line_ending.clone(),
+            spaces: self.spaces.then_some(true),
+            natural: self.natural.then_some(true),
+            collation: self.collation,
+            pin: (!self.pin_first.is_empty() || !self.pin_last.is_empty()).then(|| {
+                KeyOrder::new(vec![PinRule {
+                    path: None,
+                    first: self.pin_first.clone(),
+                    last: self.pin_last.clone(),
+                }])
+            }),
+            identity_keys: self.identity_keys.then_some(true),
+            ..Default::default()
+        }
+    }
 }
 
 impl Display for Args {
@@ -74,13 +338,58 @@ impl Display for Args {
             f,
             "Args {{
     sort arrays: {:?}
+    check: {:?}
+    collation: {:?}
+    commit: {:?}
+    commit message: {:?}
+    diff: {:?}
     dry run: {:?}
+    exclude: {:?}
+    extensions: {:?}
+    fail on: {:?}
+    files from: {:?}
+    identity keys: {:?}
+    include: {:?}
     indents: {:?}
     line ending: {:?}
+    natural: {:?}
+    only changed: {:?}
+    pin first: {:?}
+    pin last: {:?}
+    recurse submodules: {:?}
+    since: {:?}
+    staged: {:?}
+    tracked: {:?}
+    untracked: {:?}
     use spaces: {:?}
     verbose output: {:?}
 }}",
-            self.arrays, self.dry, self.indents, self.line_ending, self.spaces, self.verbose
+            self.arrays,
+            self.check,
+            self.collation,
+            self.commit,
+            self.commit_message,
+            self.diff,
+            self.dry,
+            self.exclude,
+            self.ext,
+            self.fail_on,
+            self.files_from,
+            self.identity_keys,
+            self.include,
+            self.indents,
+            self.line_ending,
+            self.natural,
+            self.only_changed,
+            self.pin_first,
+            self.pin_last,
+            self.recurse_submodules,
+            self.since,
+            self.staged,
+            self.tracked,
+            self.untracked,
+            self.spaces,
+            self.verbose
         )
     }
 }
@@ -96,11 +405,12 @@ impl log::Log for SimpleLogger {
         if self.enabled(record.metadata()) {
             match record.level() {
                 Level::Debug | Level::Trace => {
-                    eprint!(
-                        "{} - {}:{} - ",
+                    eprintln!(
+                        "{} - {}:{} - {}",
                         record.level(),
                         record.file().unwrap_or("unknown_file"),
-                        record.line().unwrap_or(0)
+                        record.line().unwrap_or(0),
+                        record.args()
                     );
                 }
                 Level::Info => eprintln!("{}", record.args()),
@@ -113,45 +423,112 @@ impl log::Log for SimpleLogger {
     fn flush(&self) {}
 }
 
-fn get_git_modified() -> Result<Vec<PathBuf>, git2::Error> {
-    let dir = env::current_dir().unwrap_or(PathBuf::from("."));
-    let repo = Repository::open(dir)?;
-    let statuses = repo.statuses(None)?;
+/// Read a list of paths separated by newlines, or NUL characters if `null` is set. Empty entries are ignored.
+fn read_files_from(source: &Path, null: bool) -> io::Result<Vec<PathBuf>> {
+    let mut input = Vec::new();
+    if source == Path::new("-") {
+        io::stdin().lock().read_to_end(&mut input)?;
+    } else {
+        input = fs::read(source)?;
+    }
 
-    let res = statuses
-        .iter()
-        .filter(|se| {
-            let s: Status = se.status();
-            // index = staged, wt + not new = tracked, unstaged
-            s.is_wt_modified() || s.is_wt_renamed() || s.is_wt_typechange()
+    let delimiter = if null { b'\0' } else { b'\n' };
+    let paths = input
+        .split(|b| *b == delimiter)
+        .map(|p| match null {
+            true => p,
+            false => p.strip_suffix(b"\r").unwrap_or(p),
         })
-        .filter_map(|s| s.path().map(PathBuf::from))
+        .filter(|p| !p.is_empty())
+        .map(path_from_bytes)
         .collect();
 
-    Ok(res)
+    Ok(paths)
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
 }
 
 fn sort_result_output(results: Vec<SortResult>) -> String {
-    let ok_count = results.iter().filter(|r| r.success()).count();
-    let fail_count = results.len() - ok_count;
+    let skipped_count = results.iter().filter(|r| r.is_skipped()).count();
+    let unsorted_count = results.iter().filter(|r| r.unsorted()).count();
+    let fail_count = results.iter().filter(|r| !r.success()).count();
+    let ok_count = results.len() - skipped_count - unsorted_count - fail_count;
 
-    let out: String;
-    if fail_count > 0 {
-        let ok = format!("{ok_count} files sorted").green();
-        let fail = format!("{fail_count} files could not be sorted").red();
-        out = format!("{ok}\n{fail}").bold().to_string();
-    } else if ok_count == 0 {
-        out = "The inputs don't lead to any json files! Exiting."
+    if ok_count + unsorted_count + fail_count == 0 {
+        return "The inputs don't lead to any json files! Exiting."
             .red()
             .to_string();
-    } else {
-        out = format!("{ok_count} files sorted")
-            .green()
-            .bold()
-            .to_string();
     }
 
-    out
+    let mut lines = vec![format!("{ok_count} files sorted").green()];
+    if unsorted_count > 0 {
+        lines.push(format!("{unsorted_count} files are not sorted").yellow());
+    }
+    if fail_count > 0 {
+        lines.push(format!("{fail_count} files could not be sorted").red());
+    }
+    let mut skipped: BTreeMap<&str, usize> = BTreeMap::new();
+    for reason in results.iter().filter_map(|r| r.skip_reason()) {
+        *skipped.entry(reason).or_default() += 1;
+    }
+    for (reason, count) in skipped {
+        lines.push(format!("{count} files skipped, {reason}").dimmed());
+    }
+
+    lines
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+        .bold()
+        .to_string()
+}
+
+fn error_exit_code(error: &JsonError) -> i32 {
+    match error {
+        JsonError::NotFound => EXIT_NOT_FOUND,
+        JsonError::ReadError => EXIT_READ_ERROR,
+        JsonError::ParseError(_) => EXIT_PARSE_ERROR,
+        JsonError::WriteError => EXIT_WRITE_ERROR,
+        JsonError::ConfigError(_) => EXIT_CONFIG_ERROR,
+    }
+}
+
+/// Exit code for a set of sort results. When several conditions apply, the highest exit code is used.
+fn sort_result_exit_code(results: &[SortResult], fail_on: &[FailOn], check: bool) -> i32 {
+    if results.iter().all(|r| r.is_skipped()) {
+        return if fail_on.contains(&FailOn::Empty) {
+            EXIT_NO_INPUTS
+        } else {
+            EXIT_OK
+        };
+    }
+
+    let error_code = results
+        .iter()
+        .filter_map(|r| r.error())
+        .filter(|e| match e {
+            JsonError::ParseError(_) => fail_on.contains(&FailOn::Parse),
+            JsonError::NotFound => fail_on.contains(&FailOn::Missing),
+            JsonError::ReadError | JsonError::WriteError | JsonError::ConfigError(_) => true,
+        })
+        .map(error_exit_code)
+        .max();
+
+    match error_code {
+        Some(code) => code,
+        None if check && results.iter().any(|r| r.unsorted()) => EXIT_UNSORTED,
+        None => EXIT_OK,
+    }
 }
 
 fn main() {
@@ -174,48 +551,149 @@ fn cli(args: Args) {
 
     log::debug!("{args}");
 
-    let indents: usize = if args.indents == 0 {
-        if args.spaces {
-            INDENT_SIZE_SPACE
-        } else {
-            INDENT_SIZE_TAB
-        }
-    } else {
-        args.indents
-    };
+    let mut settings = Settings::new(args.partial_options());
+
+    match &args.command {
+        Some(Command::PrintConfig { file }) => print_config(&mut settings, file),
+        Some(Command::Filter { action }) => git_filter(action, &args, &mut settings),
+        Some(Command::Hook { action }) => manage_hook(action),
+        Some(Command::Textconv { file, install }) => git_textconv(&args, file.as_deref(), *install),
+        Some(Command::MergeDriver {
+            base,
+            ours,
+            theirs,
+            path,
+        }) => merge_driver(&mut settings, base, ours, theirs, path.as_deref()),
+        None => (),
+    }
 
     let files: Vec<PathBuf>;
-    if args.git {
+    if args.staged {
+        log::debug!("Reading paths from git index");
+        files = vec![];
+    } else if let Some(selection) = args.git_selection() {
         log::debug!("Reading paths from git");
-        files = match get_git_modified() {
+        files = match git::select_files(&selection, args.untracked, args.recurse_submodules) {
+            Ok(f) => f,
+            Err(err) => git_fatal(err),
+        }
+    } else if let Some(source) = &args.files_from {
+        log::debug!("Reading paths from {:?}", source.to_str());
+        files = match read_files_from(source, args.null) {
             Ok(f) => f,
             Err(err) => {
-                log::debug!("Error reading git repo status: {err}");
-                log::error!("fatal: not a git repository");
-                std::process::exit(1);
+                log::error!("Error reading file list {:?}: {err}", source.to_str());
+                exit(EXIT_READ_ERROR);
             }
         }
     } else if !args.files.is_empty() {
         log::debug!("Reading paths from argv");
-        files = args.files;
+        files = args.files.clone();
     } else {
         log::debug!("Reading from stdin");
-        io_mode(&args, indents);
-        std::process::exit(0)
+        io_mode(&args, &mut settings);
+        exit(EXIT_OK)
+    }
+
+    let mode = if args.diff {
+        SortMode::Diff
+    } else if args.check {
+        SortMode::Check
+    } else if args.dry {
+        SortMode::DryRun
+    } else {
+        SortMode::Write
+    };
+
+    let (files, negations) = if args.git_selection().is_some() || args.staged {
+        (files, vec![])
+    } else {
+        let (paths, negations) = args.split_negations();
+        match args.files_from {
+            // paths given as arguments are sorted as well as the file list
+            Some(_) => ([files, paths].concat(), negations),
+            None => (paths, negations),
+        }
+    };
+    for pattern in files.iter().filter(|f| !f.exists() && is_glob(f)) {
+        if let Err(e) = GlobPattern::new(pattern) {
+            invalid_glob(e);
+        }
+    }
+
+    let excludes = [args.exclude.clone(), negations].concat();
+    let filter = match PathFilter::new(&args.include, &excludes) {
+        Ok(f) => f.with_extensions(&args.ext),
+        Err(e) => invalid_glob(e),
+    };
+    // git selects every kind of file, so only the JSON files it selects are sorted
+    let filter = match args.git_selection() {
+        Some(_) => filter.sniffing_listed(),
+        None => filter,
+    };
+
+    if args.only_changed {
+        if let Err(err) = git::open_repo() {
+            git_fatal(err);
+        }
+    }
+    if args.commit {
+        if let Err(err) = git::ensure_nothing_staged() {
+            git_fatal(err);
+        }
     }
 
-    let results = sort_files(
-        &files,
-        &args.line_ending,
-        args.spaces,
-        args.arrays,
-        indents,
-        args.dry,
-    );
+    let mut options_for = |path: &Path| {
+        let mut options = settings
+            .resolve(path)
+            .map(Resolved::file_options)
+            .map_err(JsonError::ConfigError)?;
+        if let (true, FileOptions::Sort(o)) = (args.only_changed, &mut options) {
+            o.changed_lines = git::changed_lines(path).map_err(|e| {
+                log::debug!("Error diffing {:?} against HEAD: {e}", path.to_str());
+                JsonError::ReadError
+            })?;
+        }
+        Ok(options)
+    };
+    if args.commit {
+        // only files that sorting changes are committed, find them before anything is written
+        let unsorted: Vec<PathBuf> = sort_files(&files, &filter, SortMode::Check, &mut options_for)
+            .iter()
+            .filter(|r| r.unsorted())
+            .map(|r| r.path().to_path_buf())
+            .collect();
+        if let Err(err) = git::ensure_unmodified(&unsorted) {
+            git_fatal(err);
+        }
+    }
+
+    let results = if args.staged {
+        match git::sort_staged(&filter, mode, options_for) {
+            Ok(r) => r,
+            Err(err) => git_fatal(err),
+        }
+    } else {
+        sort_files(&files, &filter, mode, options_for)
+    };
+    let exit_code = sort_result_exit_code(&results, &args.fail_on, args.check);
+    let changed: Vec<PathBuf> = results
+        .iter()
+        .filter(|r| r.changed())
+        .map(|r| r.path().to_path_buf())
+        .collect();
 
     for result in results.iter() {
-        log::info!("{result}")
+        match result.skip_reason() {
+            Some(NOT_JSON) => log::debug!("{result}"),
+            _ => log::info!("{result}"),
+        }
+    }
+
+    for diff in results.iter().filter_map(|r| r.diff()) {
+        print!("{}", colorize(diff));
     }
+    io::stdout().flush().unwrap();
 
     log::info!("");
     if args.dry {
@@ -227,9 +705,205 @@ fn cli(args: Args) {
     } else {
         log::info!("{}", sort_result_output(results))
     }
+
+    if args.commit {
+        commit_sorted(&changed, &args.commit_message, exit_code);
+    }
+
+    exit(exit_code);
 }
 
-fn io_mode(args: &Args, indents: usize) {
+fn commit_sorted(changed: &[PathBuf], message: &str, exit_code: i32) {
+    if exit_code != EXIT_OK {
+        log::warn!("Not committing, some files could not be sorted");
+    } else if changed.is_empty() {
+        log::info!("No files were changed, nothing to commit");
+    } else {
+        match git::commit_sorted(changed, message) {
+            Ok(id) => log::info!(
+                "Committed {} sorted files as {id}, and added it to .git-blame-ignore-revs",
+                changed.len()
+            ),
+            Err(err) => git_fatal(err),
+        }
+    }
+}
+
+fn git_fatal(error: git2::Error) -> ! {
+    log::debug!("Error reading git repo: {error}");
+    if error.class() == git2::ErrorClass::Repository && error.code() == git2::ErrorCode::NotFound {
+        log::error!("fatal: not a git repository");
+    } else {
+        log::error!("fatal: {}", error.message());
+    }
+    exit(EXIT_FAILURE)
+}
+
+fn invalid_glob(error: globset::Error) -> ! {
+    Args::command()
+        .error(
+            ErrorKind::ValueValidation,
+            format!("invalid glob pattern: {error}"),
+        )
+        .exit()
+}
+
+fn print_config(settings: &mut Settings, file: &Path) {
+    match settings.resolve(file) {
+        Ok(resolved) => {
+            println!("# {}", file.display());
+            print!("{resolved}");
+            exit(EXIT_OK);
+        }
+        Err(e) => {
+            log::error!("Error reading config file {e}");
+            exit(EXIT_CONFIG_ERROR);
+        }
+    }
+}
+
+fn git_filter(action: &FilterAction, args: &Args, settings: &mut Settings) {
+    match action {
+        FilterAction::Process => {
+            let filter = PathFilter::default().with_extensions(&args.ext);
+            let options_for = |path: &Path| {
+                settings
+                    .resolve(path)
+                    .map(Resolved::file_options)
+                    .map_err(JsonError::ConfigError)
+            };
+            let res = git_filter::process(io::stdin(), io::stdout(), &filter, options_for);
+            if let Err(e) = res {
+                log::error!("fatal: git filter process failed: {e}");
+                exit(EXIT_FAILURE);
+            }
+        }
+        FilterAction::Install => match git_filter::install() {
+            Ok(attributes) => log::info!("Configured git filter in {}", attributes.display()),
+            Err(e) => git_fatal(e),
+        },
+    }
+
+    exit(EXIT_OK);
+}
+
+fn git_textconv(args: &Args, file: Option<&Path>, install: bool) {
+    // clap requires a file unless --install is set
+    let file = match file {
+        Some(f) if !install => f,
+        _ => {
+            match textconv::install() {
+                Ok(attributes) => {
+                    log::info!("Configured git diff driver in {}", attributes.display())
+                }
+                Err(e) => git_fatal(e),
+            }
+            exit(EXIT_OK);
+        }
+    };
+
+    let input = match fs::read(file) {
+        Ok(i) => i,
+        Err(e) => {
+            log::error!("Error reading {}: {e}", file.display());
+            exit(EXIT_READ_ERROR);
+        }
+    };
+
+    let mut stdout = io::stdout().lock();
+    let res = match String::from_utf8(input) {
+        Ok(s) => stdout.write_all(textconv::textconv(&s, &args.partial_options()).as_bytes()),
+        // binary files are passed through for git to diff as they are
+        Err(e) => stdout.write_all(e.as_bytes()),
+    };
+    if let Err(e) = res.and_then(|_| stdout.flush()) {
+        log::error!("Error writing output: {e}");
+        exit(EXIT_WRITE_ERROR);
+    }
+    exit(EXIT_OK);
+}
+
+fn merge_driver(
+    settings: &mut Settings,
+    base: &Path,
+    ours: &Path,
+    theirs: &Path,
+    path: Option<&Path>,
+) {
+    let read = |p: &Path| match fs::read_to_string(p) {
+        Ok(s) => s,
+        Err(e) => {
+            log::error!("Error reading {}: {e}", p.display());
+            exit(EXIT_READ_ERROR);
+        }
+    };
+    let (base_input, ours_input, theirs_input) = (read(base), read(ours), read(theirs));
+
+    let path = path.unwrap_or(ours);
+    let options = match settings.resolve(path) {
+        Ok(resolved) => resolved.options,
+        Err(e) => {
+            log::error!("Error reading config file {e}");
+            exit(EXIT_CONFIG_ERROR);
+        }
+    };
+
+    let merged = merge::merge_json(&base_input, &ours_input, &theirs_input, &options);
+    let (merged, conflicts) = match merged {
+        Ok(m) => m,
+        Err(e) => {
+            // git leaves the file conflicted with our version
+            log::error!("Could not merge {}: {e}", path.display());
+            exit(error_exit_code(&e));
+        }
+    };
+
+    if let Err(e) = fs::write(ours, merged) {
+        log::error!("Error writing {}: {e}", ours.display());
+        exit(EXIT_WRITE_ERROR);
+    }
+
+    if conflicts > 0 {
+        log::warn!("{conflicts} conflicting keys in {}", path.display());
+        exit(EXIT_FAILURE);
+    }
+    exit(EXIT_OK);
+}
+
+fn manage_hook(action: &HookAction) {
+    let result = match action {
+        HookAction::Install { fix, chain } => {
+            let mode = if *fix { HookMode::Fix } else { HookMode::Check };
+            hook::install(mode, *chain)
+                .map(|p| format!("Installed pre-commit hook at {}", p.display()))
+        }
+        HookAction::Uninstall => {
+            hook::uninstall().map(|p| format!("Removed pre-commit hook at {}", p.display()))
+        }
+    };
+
+    match result {
+        Ok(message) => {
+            log::info!("{message}");
+            exit(EXIT_OK);
+        }
+        Err(HookError::Git(e)) => git_fatal(e),
+        Err(e) => {
+            log::error!("fatal: {e}");
+            exit(EXIT_FAILURE);
+        }
+    }
+}
+
+fn io_mode(args: &Args, settings: &mut Settings) {
+    let options = match settings.resolve_stdin() {
+        Ok(resolved) => resolved.options,
+        Err(e) => {
+            log::error!("Error reading config file {e}");
+            exit(EXIT_CONFIG_ERROR);
+        }
+    };
+
     let stdin = io::stdin();
     let mut input = Vec::new();
     {
@@ -237,32 +911,47 @@ fn io_mode(args: &Args, indents: usize) {
         let res = handle.read_to_end(&mut input);
         if res.is_err() || res.is_ok_and(|x| x == 0) {
             log::info!("No input");
-            exit(1);
+            exit(EXIT_FAILURE);
         }
     }
     let s_input = match String::from_utf8(input) {
         Ok(s) => s,
         Err(e) => {
             log::error!("Error parsing input : {e}");
-            exit(1);
+            exit(EXIT_READ_ERROR);
         }
     };
 
-    match sort_json_string(
-        &s_input,
-        args.spaces,
-        args.arrays,
-        &args.line_ending,
-        indents,
-    ) {
+    match sort_json_string(&s_input, &options) {
+        Ok(s) if args.check || args.diff => {
+            if args.diff {
+                print!("{}", colorize(&unified_diff(&s_input, &s, "stdin")));
+                io::stdout().flush().unwrap();
+            }
+            if args.check && s != s_input {
+                log::info!("Input is not sorted");
+                exit(EXIT_UNSORTED);
+            }
+            exit(EXIT_OK);
+        }
         Ok(s) => {
             print!("{s}");
             io::stdout().flush().unwrap();
-            exit(0);
+            exit(EXIT_OK);
         }
         Err(e) => {
-            log::error!("Error {e:?}");
-            exit(1);
+            log::error!("Error {e}");
+            if let JsonError::ParseError(parse_error) = &e {
+                log::error!("{}", parse_error.frame);
+            }
+            let result = SortResult::new(Path::new("stdin"), Some(e));
+            let exit_code = sort_result_exit_code(&[result], &args.fail_on, args.check);
+            // the error is ignored by --failOn, pass the input through unchanged
+            if exit_code == EXIT_OK && !(args.check || args.diff) {
+                print!("{s_input}");
+                io::stdout().flush().unwrap();
+            }
+            exit(exit_code);
         }
     }
 }
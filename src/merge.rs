@@ -0,0 +1,408 @@
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+
+use crate::lines::LineEnding;
+use crate::order::Step;
+use crate::sort::{sort_json_string, sort_json_string_at, JsonError, ParseError, SortOptions};
+
+/// Length of the conflict markers, the same as git's default
+const MARKER_SIZE: usize = 7;
+
+/// Both sides of a value that could not be merged. None if the side deleted the value.
+struct Conflict {
+    ours: Option<Value>,
+    theirs: Option<Value>,
+    /// Where the value is in the merged document
+    location: Vec<Step>,
+}
+
+/// Values that could not be merged, each written as a placeholder string in the merged document
+struct Conflicts {
+    /// Makes the placeholders unique, so they can not be confused with any string in the inputs
+    nonce: usize,
+    list: Vec<Conflict>,
+}
+
+impl Conflicts {
+    /// Choose a nonce so that no placeholder appears in any of the `inputs`
+    fn new(inputs: &[Option<&Value>]) -> Self {
+        let serialized: Vec<String> = inputs.iter().flatten().map(|v| v.to_string()).collect();
+        let nonce = (0..)
+            .find(|n| {
+                let prefix = Conflicts::prefix(*n);
+                !serialized.iter().any(|s| s.contains(&prefix))
+            })
+            .unwrap_or_default();
+
+        Conflicts {
+            nonce,
+            list: vec![],
+        }
+    }
+
+    fn prefix(nonce: usize) -> String {
+        format!("<roast merge conflict {nonce}:")
+    }
+
+    /// Placeholder for the conflicting value at `index`, replaced by conflict markers once the merged output is formatted
+    fn placeholder(&self, index: usize) -> String {
+        format!("{}{index}>", Conflicts::prefix(self.nonce))
+    }
+
+    /// The conflict whose placeholder is on `line`
+    fn find<'a>(&'a self, line: &'a str) -> Option<ConflictLine<'a>> {
+        let quoted = format!("\"{}", Conflicts::prefix(self.nonce));
+        let (prefix, rest) = line.split_once(&quoted)?;
+        let (index, suffix) = rest.split_once(">\"")?;
+        let conflict = self.list.get(index.parse::<usize>().ok()?)?;
+
+        Some(ConflictLine {
+            prefix,
+            conflict,
+            suffix,
+        })
+    }
+
+    fn push(&mut self, conflict: Conflict) -> Value {
+        self.list.push(conflict);
+        Value::String(self.placeholder(self.list.len() - 1))
+    }
+}
+
+fn parse(input: &str) -> Result<Option<Value>, JsonError> {
+    if input.trim().is_empty() {
+        return Ok(None);
+    }
+
+    match serde_json::from_str(input) {
+        Ok(v) => Ok(Some(v)),
+        Err(error) => Err(JsonError::ParseError(ParseError::new(&error, input))),
+    }
+}
+
+/// Three-way merge of a value. Objects are merged key by key, any other value is only merged if at most one side changed it.
+fn merge_value(
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    location: &[Step],
+    conflicts: &mut Conflicts,
+) -> Option<Value> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+
+    if let (Some(Value::Object(ours)), Some(Value::Object(theirs))) = (ours, theirs) {
+        let empty = Map::new();
+        let base = match base {
+            Some(Value::Object(b)) => b,
+            _ => &empty,
+        };
+
+        let keys: BTreeSet<&String> = base
+            .keys()
+            .chain(ours.keys())
+            .chain(theirs.keys())
+            .collect();
+        let mut merged = Map::new();
+        for key in keys {
+            let child = [location, &[Step::Key(key.clone())]].concat();
+            let (b, o, t) = (base.get(key), ours.get(key), theirs.get(key));
+            if let Some(v) = merge_value(b, o, t, &child, conflicts) {
+                merged.insert(key.clone(), v);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+
+    Some(conflicts.push(Conflict {
+        ours: ours.cloned(),
+        theirs: theirs.cloned(),
+        location: location.to_vec(),
+    }))
+}
+
+/// Format a single value found at `location` with `options`, indenting any lines after the first by `indent`
+fn format_value(
+    value: &Value,
+    location: &[Step],
+    indent: &str,
+    options: &SortOptions,
+    eol: &str,
+) -> Result<String, JsonError> {
+    let formatted = sort_json_string_at(&value.to_string(), options, location)?;
+    Ok(formatted
+        .split(eol)
+        .collect::<Vec<&str>>()
+        .join(&format!("{eol}{indent}")))
+}
+
+/// A line of the merged output holding the placeholder of a conflict
+struct ConflictLine<'a> {
+    /// Indentation and key before the placeholder
+    prefix: &'a str,
+    conflict: &'a Conflict,
+    /// Separator after the placeholder
+    suffix: &'a str,
+}
+
+/// Replace each run of lines holding placeholders with conflict markers around each side of those values
+fn mark_conflicts(
+    output: &str,
+    conflicts: &Conflicts,
+    options: &SortOptions,
+    eol: &str,
+) -> Result<String, JsonError> {
+    let mut lines: Vec<String> = vec![];
+    let mut run: Vec<ConflictLine> = vec![];
+    let mut input = output.split(eol).peekable();
+
+    while let Some(line) = input.next() {
+        let Some(found) = conflicts.find(line) else {
+            lines.push(line.to_string());
+            continue;
+        };
+        // consecutive placeholders are members of the same object, marked as a single conflict
+        run.push(found);
+        if input
+            .peek()
+            .is_some_and(|next| conflicts.find(next).is_some())
+        {
+            continue;
+        }
+        mark_run(&mut lines, &run, options, eol)?;
+        run.clear();
+    }
+
+    Ok(lines.join(eol))
+}
+
+/// Append conflict markers for a run of members to `lines`, with only the members each side kept
+fn mark_run(
+    lines: &mut Vec<String>,
+    run: &[ConflictLine],
+    options: &SortOptions,
+    eol: &str,
+) -> Result<(), JsonError> {
+    // the object has more members after the run, so every member kept needs a comma
+    let followed = run.last().is_some_and(|l| l.suffix.starts_with(','));
+
+    let side = |value: fn(&Conflict) -> &Option<Value>| -> Result<Vec<String>, JsonError> {
+        let mut members = vec![];
+        for line in run {
+            if let Some(v) = value(line.conflict) {
+                let indent: String = line
+                    .prefix
+                    .chars()
+                    .take_while(|c| c.is_whitespace())
+                    .collect();
+                let formatted = format_value(v, &line.conflict.location, &indent, options, eol)?;
+                members.push(format!("{}{formatted}", line.prefix));
+            }
+        }
+        let count = members.len();
+        for (i, member) in members.iter_mut().enumerate() {
+            if followed || i + 1 < count {
+                member.push(',');
+            }
+        }
+        Ok(members)
+    };
+    let mut ours = side(|c| &c.ours)?;
+    let mut theirs = side(|c| &c.theirs)?;
+
+    // when one side deleted every member at the end of an object, the comma after the member before them
+    // must be deleted too, so that line is moved inside the markers
+    let deleted = ours.is_empty() || theirs.is_empty();
+    if !followed && deleted && lines.last().is_some_and(|l| l.ends_with(',')) {
+        let before = lines.pop().unwrap_or_default();
+        for members in [&mut ours, &mut theirs] {
+            let line = match members.is_empty() {
+                true => before.trim_end_matches(','),
+                false => &before,
+            };
+            members.insert(0, line.to_string());
+        }
+    }
+
+    lines.push(format!("{} ours", "<".repeat(MARKER_SIZE)));
+    lines.extend(ours);
+    lines.push("=".repeat(MARKER_SIZE));
+    lines.extend(theirs);
+    lines.push(format!("{} theirs", ">".repeat(MARKER_SIZE)));
+
+    Ok(())
+}
+
+/// Three-way merge of JSON documents, returning the sorted output and the number of conflicts.
+///
+/// Objects are merged key by key, so changes to different keys never conflict.
+/// A key changed differently on each side is written with conflict markers around just that key,
+/// or around a run of adjacent conflicting keys.
+/// An empty `base` is treated as a document without any keys, e.g. when both sides added the same file.
+pub fn merge_json(
+    base: &str,
+    ours: &str,
+    theirs: &str,
+    options: &SortOptions,
+) -> Result<(String, usize), JsonError> {
+    let base = parse(base)?;
+    let ours_value = parse(ours)?;
+    let theirs_value = parse(theirs)?;
+
+    let mut conflicts =
+        Conflicts::new(&[base.as_ref(), ours_value.as_ref(), theirs_value.as_ref()]);
+    let merged = merge_value(
+        base.as_ref(),
+        ours_value.as_ref(),
+        theirs_value.as_ref(),
+        &[],
+        &mut conflicts,
+    )
+    .unwrap_or(Value::Object(Map::new()));
+
+    // the merged output is formatted from a single line, so use the line ending of our side
+    let mut options = options.clone();
+    if let LineEnding::SystemDefault = options.line_ending {
+        options.line_ending = LineEnding::parse_str(ours);
+    }
+    let eol = options.line_ending.as_str().to_string();
+
+    let mut output = sort_json_string(&merged.to_string(), &options)?;
+    let value_options = SortOptions {
+        final_newline: false,
+        ..options.clone()
+    };
+    output = mark_conflicts(&output, &conflicts, &value_options, &eol)?;
+
+    Ok((output, conflicts.list.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn options() -> SortOptions {
+        SortOptions {
+            line_ending: LineEnding::Lf,
+            use_spaces: true,
+            indents: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_different_keys() {
+        let base = r#"{"a": 1, "nested": {"x": 1}}"#;
+        let ours = r#"{"a": 1, "b": 2, "nested": {"x": 1, "y": 2}}"#;
+        let theirs = r#"{"c": 3, "a": 1, "nested": {"z": 3}}"#;
+
+        let (output, conflicts) = merge_json(base, ours, theirs, &options()).unwrap();
+
+        assert_eq!(conflicts, 0);
+        assert_eq!(
+            output,
+            "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 3,\n  \"nested\": {\n    \"y\": 2,\n    \"z\": 3\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn conflict_markers_around_key() {
+        let base = r#"{"a": 1, "b": 1, "c": 1}"#;
+        let ours = r#"{"a": 1, "b": {"x": 2}, "c": 1}"#;
+        let theirs = r#"{"a": 1, "b": 3, "c": 1}"#;
+
+        let (output, conflicts) = merge_json(base, ours, theirs, &options()).unwrap();
+
+        assert_eq!(conflicts, 1);
+        assert_eq!(
+            output,
+            "{\n  \"a\": 1,\n<<<<<<< ours\n  \"b\": {\n    \"x\": 2\n  },\n=======\n  \"b\": 3,\n>>>>>>> theirs\n  \"c\": 1\n}\n"
+        );
+    }
+
+    #[test]
+    fn conflict_with_deleted_key() {
+        let base = r#"{"a": 1, "b": 1}"#;
+        let ours = r#"{"a": 1}"#;
+        let theirs = r#"{"a": 1, "b": 2}"#;
+
+        let (output, conflicts) = merge_json(base, ours, theirs, &options()).unwrap();
+
+        assert_eq!(conflicts, 1);
+        assert_eq!(
+            output,
+            "{\n<<<<<<< ours\n  \"a\": 1\n=======\n  \"a\": 1,\n  \"b\": 2\n>>>>>>> theirs\n}\n"
+        );
+        for side in ["  \"a\": 1\n", "  \"a\": 1,\n  \"b\": 2\n"] {
+            let resolved = resolve(&output, side);
+            assert!(
+                serde_json::from_str::<Value>(&resolved).is_ok(),
+                "{resolved}"
+            );
+        }
+    }
+
+    #[test]
+    fn conflicts_with_deleted_keys() {
+        let base = r#"{"a": 1, "b": 1, "c": 1}"#;
+        let theirs = r#"{"a": 1, "b": 2, "c": 2}"#;
+
+        let (output, conflicts) = merge_json(base, r#"{"a": 1}"#, theirs, &options()).unwrap();
+
+        assert_eq!(conflicts, 2);
+        assert_eq!(
+            output,
+            "{\n<<<<<<< ours\n  \"a\": 1\n=======\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 2\n>>>>>>> theirs\n}\n"
+        );
+
+        let ours = r#"{"a": 1, "b": 3}"#;
+        let (output, _) = merge_json(base, ours, theirs, &options()).unwrap();
+
+        assert_eq!(
+            output,
+            "{\n  \"a\": 1,\n<<<<<<< ours\n  \"b\": 3\n=======\n  \"b\": 2,\n  \"c\": 2\n>>>>>>> theirs\n}\n"
+        );
+        for side in ["  \"b\": 3\n", "  \"b\": 2,\n  \"c\": 2\n"] {
+            let resolved = resolve(&output, side);
+            assert!(
+                serde_json::from_str::<Value>(&resolved).is_ok(),
+                "{resolved}"
+            );
+        }
+    }
+
+    /// Replace the conflict markers in `output` with `side`
+    fn resolve(output: &str, side: &str) -> String {
+        let start = output.find("<<<<<<<").unwrap();
+        let end = output.find(">>>>>>> theirs\n").unwrap() + ">>>>>>> theirs\n".len();
+        format!("{}{side}{}", &output[..start], &output[end..])
+    }
+
+    #[test]
+    fn string_like_placeholder() {
+        let placeholder = "<roast merge conflict 0:0>";
+        let base = format!(r#"{{"a": "{placeholder}", "b": 1}}"#);
+        let ours = format!(r#"{{"a": "{placeholder}", "b": 2}}"#);
+        let theirs = format!(r#"{{"a": "{placeholder}", "b": 3}}"#);
+
+        let (output, conflicts) = merge_json(&base, &ours, &theirs, &options()).unwrap();
+
+        assert_eq!(conflicts, 1);
+        assert_eq!(
+            output,
+            format!("{{\n  \"a\": \"{placeholder}\",\n<<<<<<< ours\n  \"b\": 2\n=======\n  \"b\": 3\n>>>>>>> theirs\n}}\n")
+        );
+    }
+
+    #[test]
+    fn both_added() {
+        let (output, conflicts) = merge_json("", r#"{"a": 1}"#, r#"{"b": 2}"#, &options()).unwrap();
+
+        assert_eq!(conflicts, 0);
+        assert_eq!(output, "{\n  \"a\": 1,\n  \"b\": 2\n}\n");
+    }
+}
@@ -0,0 +1,762 @@
+use regex::Regex;
+use serde::de::Error as _;
+use serde::ser::{SerializeMap, SerializeSeq};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::Display;
+
+use crate::collate::Collation;
+
+/// A key to pin in objects, either a literal key or a regex written between slashes, e.g. `/^x-/`
+#[derive(Clone, Debug)]
+pub struct KeyPattern {
+    source: String,
+    regex: Option<Regex>,
+}
+
+impl KeyPattern {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let regex = match s.strip_prefix('/').and_then(|r| r.strip_suffix('/')) {
+            Some(r) if s.len() > 1 => {
+                Some(Regex::new(r).map_err(|e| format!("invalid key pattern {s}: {e}"))?)
+            }
+            _ => None,
+        };
+
+        Ok(KeyPattern {
+            source: s.to_string(),
+            regex,
+        })
+    }
+
+    fn is_match(&self, key: &str) -> bool {
+        match &self.regex {
+            Some(r) => r.is_match(key),
+            None => self.source == key,
+        }
+    }
+}
+
+/// One step from a JSON value to a value inside it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// One segment of a [JsonPath]
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    AnyKey,
+    Index(usize),
+    AnyIndex,
+    /// A JSON pointer token, which is a key or an array index
+    Token(String),
+    /// `*` in a JSON pointer, any key or array element
+    Any,
+}
+
+/// A path to values in a JSON file, e.g. `$.definitions.*.properties` or `$.items[*]`.
+/// `*` matches any key, `[*]` matches any array element.
+///
+/// Paths may also be written as a JSON pointer, e.g. `/dependencies/*/features`, where `*` matches any key or element.
+#[derive(Clone, Debug)]
+pub struct JsonPath {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if s.starts_with('/') {
+            return Ok(JsonPath::parse_pointer(s));
+        }
+
+        let invalid = |reason: &str| format!("invalid JSON path {s}: {reason}");
+        let mut rest = s.strip_prefix('$').unwrap_or(s);
+        let mut segments = vec![];
+
+        while !rest.is_empty() {
+            if let Some(r) = rest.strip_prefix('.') {
+                let end = r.find(['.', '[']).unwrap_or(r.len());
+                segments.push(match &r[..end] {
+                    "" => return Err(invalid("empty key")),
+                    "*" => Segment::AnyKey,
+                    key => Segment::Key(key.to_string()),
+                });
+                rest = &r[end..];
+            } else if let Some(r) = rest.strip_prefix('[') {
+                let end = r.find(']').ok_or_else(|| invalid("missing ]"))?;
+                let inner = &r[..end];
+                segments.push(if inner == "*" {
+                    Segment::AnyIndex
+                } else if let Ok(index) = inner.parse() {
+                    Segment::Index(index)
+                } else if let Ok(key) = serde_json::from_str::<String>(inner) {
+                    Segment::Key(key)
+                } else {
+                    return Err(invalid("expected an index, * or a quoted key between []"));
+                });
+                rest = &r[end + 1..];
+            } else {
+                return Err(invalid("expected . or ["));
+            }
+        }
+
+        Ok(JsonPath {
+            source: s.to_string(),
+            segments,
+        })
+    }
+
+    fn parse_pointer(s: &str) -> Self {
+        let segments = s
+            .split('/')
+            .skip(1)
+            .map(|token| match token {
+                "*" => Segment::Any,
+                t => Segment::Token(t.replace("~1", "/").replace("~0", "~")),
+            })
+            .collect();
+
+        JsonPath {
+            source: s.to_string(),
+            segments,
+        }
+    }
+
+    fn is_match(&self, location: &[Step]) -> bool {
+        self.segments.len() == location.len()
+            && self
+                .segments
+                .iter()
+                .zip(location)
+                .all(|(s, l)| match (s, l) {
+                    (Segment::Key(k), Step::Key(key)) => k == key,
+                    (Segment::AnyKey, Step::Key(_)) => true,
+                    (Segment::Index(i), Step::Index(index)) => i == index,
+                    (Segment::AnyIndex, Step::Index(_)) => true,
+                    (Segment::Token(t), Step::Key(key)) => t == key,
+                    (Segment::Token(t), Step::Index(index)) => t.parse() == Ok(*index),
+                    (Segment::Any, _) => true,
+                    _ => false,
+                })
+    }
+}
+
+/// Keys to pin first and last in objects, in every object or only in objects at `path`
+#[derive(Clone, Debug)]
+pub struct PinRule {
+    pub path: Option<JsonPath>,
+    pub first: Vec<KeyPattern>,
+    pub last: Vec<KeyPattern>,
+}
+
+/// Order of the keys in JSON objects. Keys are sorted, except for any keys pinned by a [PinRule].
+///
+/// Keys matching a `first` pattern are moved to the start of the object, in the order of the patterns,
+/// and keys matching a `last` pattern to the end. Keys matching the same pattern are sorted.
+/// Rules for a path take precedence over global rules.
+#[derive(Clone, Debug, Default)]
+pub struct KeyOrder {
+    rules: Vec<PinRule>,
+}
+
+impl KeyOrder {
+    pub fn new(rules: Vec<PinRule>) -> Self {
+        KeyOrder { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Sort the keys of the object at `location` with `collation`, then move any pinned keys
+    pub fn order_keys<K: AsRef<str>>(
+        &self,
+        location: &[Step],
+        collation: &Collation,
+        keys: &mut [K],
+    ) {
+        keys.sort_by(|a, b| collation.cmp_keys(a.as_ref(), b.as_ref()));
+        self.sort_keys(location, keys);
+    }
+
+    /// Move the pinned keys of the object at `location`
+    fn sort_keys<K: AsRef<str>>(&self, location: &[Step], keys: &mut [K]) {
+        let scoped = self
+            .rules
+            .iter()
+            .filter(|r| r.path.as_ref().is_some_and(|p| p.is_match(location)));
+        let global = self.rules.iter().filter(|r| r.path.is_none());
+        let rules: Vec<&PinRule> = scoped.chain(global).collect();
+        if rules.is_empty() {
+            return;
+        }
+
+        // each key is pinned by the first rule that matches it, so a rule for a path overrides a global rule
+        let rank = |key: &str| {
+            for (r, rule) in rules.iter().enumerate() {
+                if let Some(i) = rule.first.iter().position(|p| p.is_match(key)) {
+                    return (0, r, i);
+                }
+                if let Some(i) = rule.last.iter().position(|p| p.is_match(key)) {
+                    return (2, r, i);
+                }
+            }
+            (1, 0, 0)
+        };
+
+        // stable, so keys with the same rank stay sorted
+        keys.sort_by_cached_key(|k| rank(k.as_ref()));
+    }
+}
+
+impl Display for KeyOrder {
+    /// Rules as `[[pin]]` tables, as they are written in a `roast.toml` config file
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |patterns: &[KeyPattern]| {
+            let quoted: Vec<String> = patterns.iter().map(|p| format!("{:?}", p.source)).collect();
+            format!("[{}]", quoted.join(", "))
+        };
+        for rule in self.rules.iter() {
+            writeln!(f, "[[pin]]")?;
+            if let Some(path) = &rule.path {
+                writeln!(f, "path = {:?}", path.source)?;
+            }
+            writeln!(f, "first = {}", list(&rule.first))?;
+            writeln!(f, "last = {}", list(&rule.last))?;
+        }
+        Ok(())
+    }
+}
+
+/// A `[[pin]]` table in a config file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PinConfig {
+    path: Option<String>,
+    #[serde(default)]
+    first: Vec<String>,
+    #[serde(default)]
+    last: Vec<String>,
+}
+
+impl PinConfig {
+    fn into_rule(self) -> Result<PinRule, String> {
+        let patterns = |list: Vec<String>| {
+            list.iter()
+                .map(|p| KeyPattern::parse(p))
+                .collect::<Result<Vec<KeyPattern>, String>>()
+        };
+
+        Ok(PinRule {
+            path: self.path.as_deref().map(JsonPath::parse).transpose()?,
+            first: patterns(self.first)?,
+            last: patterns(self.last)?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyOrder {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let rules = Vec::<PinConfig>::deserialize(deserializer)?
+            .into_iter()
+            .map(PinConfig::into_rule)
+            .collect::<Result<Vec<PinRule>, String>>()
+            .map_err(D::Error::custom)?;
+
+        Ok(KeyOrder { rules })
+    }
+}
+
+/// How to sort the arrays at a [JsonPath]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArrayStrategy {
+    /// Sort arrays of strings, numbers, booleans and nulls
+    Scalars,
+    /// Sort arrays of objects by the values of one or more of their keys
+    Objects,
+    /// Never sort the array
+    None,
+}
+
+impl ArrayStrategy {
+    fn name(&self) -> &'static str {
+        match self {
+            ArrayStrategy::Scalars => "scalars",
+            ArrayStrategy::Objects => "objects",
+            ArrayStrategy::None => "none",
+        }
+    }
+}
+
+/// Sort the arrays at `path` with `strategy`.
+/// With [ArrayStrategy::Objects], `by` lists the keys to compare, in order of precedence
+#[derive(Clone, Debug)]
+pub struct ArrayRule {
+    pub path: JsonPath,
+    pub strategy: ArrayStrategy,
+    pub by: Vec<String>,
+}
+
+impl ArrayRule {
+    /// Sort `list` in place, unless it contains values the strategy can not sort.
+    /// Returns true if the array was sorted.
+    pub fn sort(&self, list: &mut [Value], collation: &Collation) -> bool {
+        match self.strategy {
+            ArrayStrategy::Scalars if list.iter().all(is_scalar) => {
+                list.sort_by(|a, b| cmp_scalars(a, b, collation));
+                true
+            }
+            ArrayStrategy::Objects if list.iter().all(Value::is_object) => {
+                list.sort_by(|a, b| {
+                    self.by.iter().fold(Ordering::Equal, |order, key| {
+                        order.then_with(|| cmp_scalars(&a[key], &b[key], collation))
+                    })
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Strategies for sorting the arrays at specific paths, the first rule matching an array's path is used.
+/// Arrays that do not match any rule are sorted as set by the `arrays` option.
+#[derive(Clone, Debug, Default)]
+pub struct ArrayOrder {
+    rules: Vec<ArrayRule>,
+}
+
+impl ArrayOrder {
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The rule for the array at `location`
+    pub fn rule_at(&self, location: &[Step]) -> Option<&ArrayRule> {
+        self.rules.iter().find(|r| r.path.is_match(location))
+    }
+}
+
+impl Display for ArrayOrder {
+    /// Rules as `[[array]]` tables, as they are written in a `roast.toml` config file
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rule in self.rules.iter() {
+            writeln!(f, "[[array]]")?;
+            writeln!(f, "path = {:?}", rule.path.source)?;
+            writeln!(f, "strategy = \"{}\"", rule.strategy.name())?;
+            if !rule.by.is_empty() {
+                writeln!(f, "by = {:?}", rule.by)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An `[[array]]` table in a config file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ArrayConfig {
+    path: String,
+    strategy: ArrayStrategy,
+    #[serde(default)]
+    by: Vec<String>,
+}
+
+impl ArrayConfig {
+    fn into_rule(self) -> Result<ArrayRule, String> {
+        match (self.strategy, self.by.is_empty()) {
+            (ArrayStrategy::Objects, true) => {
+                return Err(format!("array strategy for {} is missing `by`", self.path))
+            }
+            (ArrayStrategy::Scalars | ArrayStrategy::None, false) => {
+                return Err(format!(
+                    "`by` is only used by the \"objects\" array strategy, in {}",
+                    self.path
+                ))
+            }
+            _ => (),
+        }
+
+        Ok(ArrayRule {
+            path: JsonPath::parse(&self.path)?,
+            strategy: self.strategy,
+            by: self.by,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ArrayOrder {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let rules = Vec::<ArrayConfig>::deserialize(deserializer)?
+            .into_iter()
+            .map(ArrayConfig::into_rule)
+            .collect::<Result<Vec<ArrayRule>, String>>()
+            .map_err(D::Error::custom)?;
+
+        Ok(ArrayOrder { rules })
+    }
+}
+
+fn is_scalar(value: &Value) -> bool {
+    !(value.is_array() || value.is_object())
+}
+
+/// Compare values of any type, ordered by type then by value. Nulls, and missing values, are last.
+/// Strings are compared with `collation`, objects and arrays are all equal.
+fn cmp_scalars(a: &Value, b: &Value, collation: &Collation) -> Ordering {
+    let rank = |v: &Value| match v {
+        Value::Bool(_) => 0,
+        Value::Number(_) => 1,
+        Value::String(_) => 2,
+        Value::Array(_) | Value::Object(_) => 3,
+        Value::Null => 4,
+    };
+
+    match (a, b) {
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::Number(x), Value::Number(y)) => match (x.as_i64(), y.as_i64()) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            _ => x
+                .as_f64()
+                .unwrap_or_default()
+                .total_cmp(&y.as_f64().unwrap_or_default()),
+        },
+        (Value::String(x), Value::String(y)) => collation.cmp_strings(x, y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Members that identify an object in an array, in order of preference
+pub const IDENTITY_KEYS: [&str; 3] = ["id", "name", "key"];
+
+/// The first of [IDENTITY_KEYS] that every object in `list` has, with a string or number value that is
+/// unique in `list`. None if any element is not an object, or no member identifies them.
+pub fn identity_key(list: &[Value]) -> Option<&'static str> {
+    if list.len() < 2 || !list.iter().all(Value::is_object) {
+        return None;
+    }
+
+    IDENTITY_KEYS.into_iter().find(|key| {
+        let mut seen = HashSet::with_capacity(list.len());
+        list.iter().all(|item| match &item[*key] {
+            Value::String(s) => seen.insert((true, s.clone())),
+            Value::Number(n) => seen.insert((false, n.to_string())),
+            _ => false,
+        })
+    })
+}
+
+/// Sort an array of objects by the member found by [identity_key], which is returned.
+/// The array is left alone if there is none.
+pub fn sort_by_identity(list: &mut [Value], collation: &Collation) -> Option<&'static str> {
+    let key = identity_key(list)?;
+    list.sort_by(|a, b| cmp_scalars(&a[key], &b[key], collation));
+    Some(key)
+}
+
+/// `location` written as a JSON path, e.g. `$.users[0]["first name"]`
+pub fn display_location(location: &[Step]) -> String {
+    let mut path = String::from("$");
+    for step in location {
+        match step {
+            Step::Index(i) => path.push_str(&format!("[{i}]")),
+            Step::Key(k) if !k.is_empty() && k.chars().all(|c| c.is_alphanumeric() || c == '_') => {
+                path.push('.');
+                path.push_str(k);
+            }
+            Step::Key(k) => path.push_str(&format!("[{k:?}]")),
+        }
+    }
+    path
+}
+
+/// Serializes a [Value] with the keys of each object sorted by [Collation], then pinned by [KeyOrder]
+pub struct Ordered<'a> {
+    value: &'a Value,
+    order: &'a KeyOrder,
+    collation: Collation,
+    location: Vec<Step>,
+}
+
+impl<'a> Ordered<'a> {
+    /// `value` found at `location` in a file
+    pub fn new(
+        value: &'a Value,
+        order: &'a KeyOrder,
+        collation: Collation,
+        location: &[Step],
+    ) -> Self {
+        Ordered {
+            value,
+            order,
+            collation,
+            location: location.to_vec(),
+        }
+    }
+
+    fn child(&self, value: &'a Value, step: Step) -> Self {
+        let mut location = self.location.clone();
+        location.push(step);
+        Ordered {
+            value,
+            order: self.order,
+            collation: self.collation,
+            location,
+        }
+    }
+}
+
+impl Serialize for Ordered<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.value {
+            Value::Object(obj) => {
+                let mut keys: Vec<&String> = obj.keys().collect();
+                self.order
+                    .order_keys(&self.location, &self.collation, &mut keys);
+
+                let mut map = serializer.serialize_map(Some(keys.len()))?;
+                for key in keys {
+                    let child = self.child(&obj[key], Step::Key(key.clone()));
+                    map.serialize_entry(key, &child)?;
+                }
+                map.end()
+            }
+            Value::Array(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for (i, item) in list.iter().enumerate() {
+                    seq.serialize_element(&self.child(item, Step::Index(i)))?;
+                }
+                seq.end()
+            }
+            other => other.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rule(path: Option<&str>, first: &[&str], last: &[&str]) -> PinRule {
+        let patterns = |list: &[&str]| list.iter().map(|p| KeyPattern::parse(p).unwrap()).collect();
+        PinRule {
+            path: path.map(|p| JsonPath::parse(p).unwrap()),
+            first: patterns(first),
+            last: patterns(last),
+        }
+    }
+
+    fn ordered(order: &KeyOrder, input: &str) -> String {
+        let value: Value = serde_json::from_str(input).unwrap();
+        let ordered = Ordered::new(&value, order, Collation::default(), &[]);
+        serde_json::to_string(&ordered).unwrap()
+    }
+
+    #[test]
+    fn pin_first_and_last() {
+        let order = KeyOrder::new(vec![rule(
+            None,
+            &["$schema", "name", "version"],
+            &["/^x-/"],
+        )]);
+        let input = r#"{"x-b": 1, "version": 1, "a": 1, "x-a": 1, "name": 1, "$schema": 1, "c": {"name": 1, "b": 1}}"#;
+
+        assert_eq!(
+            ordered(&order, input),
+            r#"{"$schema":1,"name":1,"version":1,"a":1,"c":{"name":1,"b":1},"x-a":1,"x-b":1}"#
+        );
+    }
+
+    #[test]
+    fn pin_under_path() {
+        let order = KeyOrder::new(vec![
+            rule(Some("$.definitions.*"), &["type"], &[]),
+            rule(None, &["id"], &["type"]),
+        ]);
+        let input = r#"{"type": 1, "id": 1, "definitions": {"a": {"b": 1, "type": 1, "id": 1}}}"#;
+
+        assert_eq!(
+            ordered(&order, input),
+            r#"{"id":1,"definitions":{"a":{"type":1,"id":1,"b":1}},"type":1}"#
+        );
+    }
+
+    #[test]
+    fn path_rules_override_global_rules() {
+        let order = KeyOrder::new(vec![
+            rule(None, &["id"], &["name"]),
+            rule(Some("$.items[*]"), &["name"], &["id"]),
+        ]);
+        let input = r#"{"name": 1, "a": 1, "id": 1, "items": [{"id": 1, "a": 1, "name": 1}]}"#;
+
+        assert_eq!(
+            ordered(&order, input),
+            r#"{"id":1,"a":1,"items":[{"name":1,"a":1,"id":1}],"name":1}"#
+        );
+    }
+
+    #[test]
+    fn parse_paths() {
+        let path = JsonPath::parse(r#"$.items[*].a["b.c"][0]"#).unwrap();
+        assert_eq!(
+            path.segments,
+            vec![
+                Segment::Key("items".into()),
+                Segment::AnyIndex,
+                Segment::Key("a".into()),
+                Segment::Key("b.c".into()),
+                Segment::Index(0),
+            ]
+        );
+        assert!(JsonPath::parse("$").unwrap().is_match(&[]));
+        assert!(JsonPath::parse("$.a.").is_err());
+        assert!(JsonPath::parse("$a").is_err());
+
+        let pointer = JsonPath::parse("/dependencies/*/features/0/a~1b").unwrap();
+        assert!(pointer.is_match(&[
+            Step::Key("dependencies".into()),
+            Step::Key("serde".into()),
+            Step::Key("features".into()),
+            Step::Index(0),
+            Step::Key("a/b".into()),
+        ]));
+        assert!(pointer.is_match(&[
+            Step::Key("dependencies".into()),
+            Step::Index(2),
+            Step::Key("features".into()),
+            Step::Key("0".into()),
+            Step::Key("a/b".into()),
+        ]));
+        assert!(!pointer.is_match(&[Step::Key("dependencies".into())]));
+
+        assert!(KeyPattern::parse("/[/").is_err());
+        assert!(KeyPattern::parse("/").unwrap().is_match("/"));
+    }
+
+    fn array_rule(strategy: ArrayStrategy, by: &[&str]) -> ArrayRule {
+        ArrayRule {
+            path: JsonPath::parse("$").unwrap(),
+            strategy,
+            by: by.iter().map(|k| k.to_string()).collect(),
+        }
+    }
+
+    fn sorted(rule: &ArrayRule, input: &str) -> (bool, String) {
+        let mut list: Vec<Value> = serde_json::from_str(input).unwrap();
+        let sorted = rule.sort(&mut list, &Collation::default());
+        (sorted, serde_json::to_string(&list).unwrap())
+    }
+
+    #[test]
+    fn sort_scalars() {
+        let rule = array_rule(ArrayStrategy::Scalars, &[]);
+        assert_eq!(
+            sorted(&rule, r#"[null, "b", 10, true, "A", 9, false, 1.5]"#),
+            (true, r#"[false,true,1.5,9,10,"A","b",null]"#.into())
+        );
+        assert_eq!(
+            sorted(&rule, r#"[2, {"a": 1}, 1]"#),
+            (false, r#"[2,{"a":1},1]"#.into())
+        );
+    }
+
+    #[test]
+    fn sort_objects_by_keys() {
+        let rule = array_rule(ArrayStrategy::Objects, &["name", "version"]);
+        let input = r#"[{"name": "b"}, {"version": 1}, {"name": "a", "version": 2}, {"name": null}, {"name": "a", "version": 1}]"#;
+        assert_eq!(
+            sorted(&rule, input),
+            (
+                true,
+                r#"[{"name":"a","version":1},{"name":"a","version":2},{"name":"b"},{"version":1},{"name":null}]"#
+                    .into()
+            )
+        );
+        assert!(!sorted(&rule, r#"[{"name": "b"}, 1]"#).0);
+    }
+
+    #[test]
+    fn leave_array_alone() {
+        let rule = array_rule(ArrayStrategy::None, &[]);
+        assert_eq!(
+            sorted(&rule, r#"["b", "a"]"#),
+            (false, r#"["b","a"]"#.into())
+        );
+    }
+
+    fn by_identity(input: &str) -> (Option<&'static str>, String) {
+        let mut value: Value = serde_json::from_str(input).unwrap();
+        let key = sort_by_identity(value.as_array_mut().unwrap(), &Collation::default());
+        (key, serde_json::to_string(&value).unwrap())
+    }
+
+    #[test]
+    fn sort_by_identity_key() {
+        assert_eq!(
+            by_identity(r#"[{"id": 10, "name": "a"}, {"id": 2, "name": "a"}]"#),
+            (
+                Some("id"),
+                r#"[{"id":2,"name":"a"},{"id":10,"name":"a"}]"#.into()
+            )
+        );
+        // ids are not unique, so sorted by name
+        assert_eq!(
+            by_identity(r#"[{"id": 1, "name": "b"}, {"id": 1, "name": "a"}]"#),
+            (
+                Some("name"),
+                r#"[{"id":1,"name":"a"},{"id":1,"name":"b"}]"#.into()
+            )
+        );
+        assert_eq!(
+            by_identity(r#"[{"key": "b"}, {"key": "a", "id": 1}]"#),
+            (Some("key"), r#"[{"id":1,"key":"a"},{"key":"b"}]"#.into())
+        );
+    }
+
+    #[test]
+    fn no_identity_key() {
+        for input in [
+            r#"[{"id": 2}, {"id": 1}, {"id": 2}]"#,
+            r#"[{"id": 2}, {"name": "a"}]"#,
+            r#"[{"id": null}, {"id": 1}]"#,
+            r#"[{"id": [2]}, {"id": [1]}]"#,
+            r#"[{"id": 2}, "a"]"#,
+            r#"[{"value": 2}, {"value": 1}]"#,
+        ] {
+            assert_eq!(
+                by_identity(input),
+                (
+                    None,
+                    serde_json::to_string(&serde_json::from_str::<Value>(input).unwrap()).unwrap()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn display_locations() {
+        let location = [
+            Step::Key("users".into()),
+            Step::Index(0),
+            Step::Key("first name".into()),
+        ];
+        assert_eq!(display_location(&location), r#"$.users[0]["first name"]"#);
+        assert_eq!(display_location(&[]), "$");
+    }
+}
@@ -0,0 +1,182 @@
+use colored::*;
+use serde_json::error::Category;
+use std::fmt::Display;
+
+/// Number of lines to show before the offending line in a code frame
+const FRAME_CONTEXT_LINES: usize = 2;
+
+/// Location and category of a JSON syntax error
+///
+///  * `line` - 1 based line number of the error
+///  * `column` - 1 based column number of the error, in bytes
+///  * `category` - kind of error, e.g. a syntax error or unexpected end of input
+///  * `message` - description of the error, without the location
+///  * `frame` - lines of the input surrounding the error, with a caret under the offending character
+///
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub category: Category,
+    pub message: String,
+    pub frame: String,
+}
+
+impl ParseError {
+    pub fn new(error: &serde_json::Error, input: &str) -> Self {
+        let location = format!(" at line {} column {}", error.line(), error.column());
+        let message = error.to_string();
+        let message = message
+            .strip_suffix(&location)
+            .unwrap_or(&message)
+            .to_string();
+
+        let (line, column) = error_location(input, error.line(), error.column());
+
+        ParseError {
+            line,
+            column,
+            category: error.classify(),
+            message,
+            frame: code_frame(input, line, column),
+        }
+    }
+
+    fn category_str(&self) -> &str {
+        match self.category {
+            Category::Io => "io error",
+            Category::Syntax => "syntax error",
+            Category::Data => "data error",
+            Category::Eof => "unexpected end of input",
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} at line {} column {}",
+            self.category_str(),
+            self.message,
+            self.line,
+            self.column
+        )
+    }
+}
+
+/// serde_json reports errors found at a line break as column 0 of the next line,
+/// move these to the end of the line the error was found on.
+fn error_location(input: &str, line: usize, column: usize) -> (usize, usize) {
+    if column > 0 || line < 2 {
+        return (line, column);
+    }
+
+    match input.split('\n').nth(line - 2) {
+        Some(text) => (line - 1, text.trim_end_matches('\r').len() + 1),
+        None => (line, column),
+    }
+}
+
+/// Render the lines of `input` leading up to `line`, with a caret under `column`.
+///
+/// ```text
+///   2 |     "package": true,
+/// > 3 |   }
+///     |   ^
+/// ```
+fn code_frame(input: &str, line: usize, column: usize) -> String {
+    let lines: Vec<&str> = input.split('\n').collect();
+    if line == 0 || line > lines.len() {
+        return String::new();
+    }
+
+    let first = line.saturating_sub(FRAME_CONTEXT_LINES).max(1);
+    let gutter_width = line.to_string().len();
+
+    let mut frame = String::new();
+    for n in first..=line {
+        let text = lines[n - 1].trim_end_matches('\r');
+        let marker = if n == line { ">" } else { " " };
+        frame += &format!("{marker} {n:>gutter_width$} | {text}\n");
+    }
+
+    // serde_json counts columns in bytes, align the caret by character instead.
+    // Tabs are kept so the caret lines up with tab indented input.
+    let text = lines[line - 1];
+    let prefix_len = column.saturating_sub(1);
+    let padding: String = text
+        .char_indices()
+        .take_while(|(i, _)| *i < prefix_len)
+        .map(|(_, c)| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    frame += &format!("  {:gutter_width$} | {padding}{}", "", "^".red().bold());
+
+    frame
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse_error(input: &str) -> ParseError {
+        let error = serde_json::from_str::<serde_json::Value>(input).unwrap_err();
+        ParseError::new(&error, input)
+    }
+
+    #[test]
+    fn trailing_comma() {
+        colored::control::set_override(false);
+        let error = parse_error("{\n  \"a\": 1,\n}");
+
+        assert_eq!(error.line, 3);
+        assert_eq!(error.column, 1);
+        assert_eq!(error.category, Category::Syntax);
+        assert_eq!(
+            error.to_string(),
+            "syntax error: trailing comma at line 3 column 1"
+        );
+        assert_eq!(error.frame, "  1 | {\n  2 |   \"a\": 1,\n> 3 | }\n    | ^");
+    }
+
+    #[test]
+    fn caret_column() {
+        colored::control::set_override(false);
+        let error = parse_error("{\"a\": tru}");
+
+        assert_eq!(error.frame, "> 1 | {\"a\": tru}\n    |          ^");
+    }
+
+    #[test]
+    fn caret_keeps_tabs() {
+        colored::control::set_override(false);
+        let error = parse_error("{\n\t\"a\": nope\n}");
+
+        assert_eq!(error.frame, "  1 | {\n> 2 | \t\"a\": nope\n    | \t      ^");
+    }
+
+    #[test]
+    fn error_at_line_break() {
+        colored::control::set_override(false);
+        let error = parse_error("{\n  \"a\": tru\n}");
+
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 11);
+        assert_eq!(
+            error.frame,
+            "  1 | {\n> 2 |   \"a\": tru\n    |           ^"
+        );
+    }
+
+    #[test]
+    fn unexpected_end() {
+        colored::control::set_override(false);
+        let error = parse_error("{\"a\": [1, 2");
+
+        assert_eq!(error.category, Category::Eof);
+        assert_eq!(
+            error.to_string(),
+            "unexpected end of input: EOF while parsing a list at line 1 column 11"
+        );
+    }
+}
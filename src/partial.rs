@@ -0,0 +1,443 @@
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::ops::{Range, RangeInclusive};
+
+use crate::order::Step;
+use crate::sort::{sort_array, sort_json_string, JsonError, ParseError, SortOptions};
+
+/// An object or array in JSON text
+#[derive(Debug, PartialEq, Eq)]
+struct Container {
+    /// Byte offsets, including the opening and closing brackets
+    bytes: Range<usize>,
+    /// Location of the container in the root value
+    location: Vec<Step>,
+}
+
+/// An object or array that has been opened but not yet closed
+struct Open {
+    start: usize,
+    location: Vec<Step>,
+    is_object: bool,
+    key: String,
+    index: usize,
+}
+
+/// The objects and arrays in JSON text, outermost first
+fn containers(input: &str) -> Vec<Container> {
+    let mut containers = vec![];
+    let mut open: Vec<Open> = vec![];
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut string_start = 0;
+    let mut last_string = String::new();
+
+    for (i, b) in input.bytes().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => {
+                    in_string = false;
+                    last_string =
+                        serde_json::from_str(&input[string_start..=i]).unwrap_or_default();
+                }
+                _ => (),
+            }
+            continue;
+        }
+
+        match b {
+            b'"' => {
+                in_string = true;
+                string_start = i;
+            }
+            b':' => {
+                if let Some(parent) = open.last_mut().filter(|o| o.is_object) {
+                    parent.key = std::mem::take(&mut last_string);
+                }
+            }
+            b',' => {
+                if let Some(parent) = open.last_mut().filter(|o| !o.is_object) {
+                    parent.index += 1;
+                }
+            }
+            b'{' | b'[' => {
+                let location = match open.last() {
+                    Some(parent) if parent.is_object => {
+                        [parent.location.clone(), vec![Step::Key(parent.key.clone())]].concat()
+                    }
+                    Some(parent) => {
+                        [parent.location.clone(), vec![Step::Index(parent.index)]].concat()
+                    }
+                    None => vec![],
+                };
+                open.push(Open {
+                    start: i,
+                    location,
+                    is_object: b == b'{',
+                    key: String::new(),
+                    index: 0,
+                });
+            }
+            b'}' | b']' => {
+                if let Some(o) = open.pop() {
+                    containers.push(Container {
+                        bytes: o.start..i + 1,
+                        location: o.location,
+                    });
+                }
+            }
+            _ => (),
+        }
+    }
+
+    containers.sort_by_key(|c| c.bytes.start);
+    containers
+}
+
+/// Byte offsets of 1 based `lines` in `input`, without leading and trailing whitespace
+fn line_span(
+    input: &str,
+    line_starts: &[usize],
+    lines: &RangeInclusive<usize>,
+) -> Option<Range<usize>> {
+    let first = lines.start().max(&1) - 1;
+    let last = (*lines.end()).min(line_starts.len()).checked_sub(1)?;
+    if first > last {
+        return None;
+    }
+
+    let start = line_starts[first];
+    let end = line_starts.get(last + 1).copied().unwrap_or(input.len());
+    let text = &input[start..end];
+    let trimmed = text.trim_start();
+    let start = start + (text.len() - trimmed.len());
+    let end = start + trimmed.trim_end().len();
+
+    (start < end).then_some(start..end)
+}
+
+/// A key and value in an object, or an element of an array
+struct Member {
+    /// Position of the member in the container
+    index: usize,
+    /// Byte offsets in the container, without any surrounding whitespace or commas
+    bytes: Range<usize>,
+    /// Key of an object member, empty for array elements
+    key: String,
+}
+
+impl AsRef<str> for Member {
+    fn as_ref(&self) -> &str {
+        &self.key
+    }
+}
+
+/// The members of the object or array `text`, which starts with its opening bracket
+fn members(text: &str) -> Vec<Member> {
+    let mut members = vec![];
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = None;
+    let mut end = 0;
+
+    for (i, b) in text.bytes().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => {
+                    in_string = false;
+                    if depth == 1 {
+                        end = i + 1;
+                    }
+                }
+                _ => (),
+            }
+            continue;
+        }
+
+        match b {
+            b'"' => in_string = true,
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            b',' if depth == 1 => {
+                members.extend(start.take().map(|s| s..end));
+                continue;
+            }
+            _ => (),
+        }
+
+        // the brackets of the container itself are not part of a member
+        if depth == 0 || (i == 0 && depth == 1) {
+            continue;
+        }
+        if !b.is_ascii_whitespace() {
+            start.get_or_insert(i);
+            end = i + 1;
+        }
+    }
+    members.extend(start.map(|s| s..end));
+
+    members
+        .into_iter()
+        .enumerate()
+        .map(|(index, bytes)| {
+            let key = serde_json::Deserializer::from_str(&text[bytes.clone()])
+                .into_iter::<String>()
+                .next()
+                .and_then(Result::ok)
+                .unwrap_or_default();
+            Member { index, bytes, key }
+        })
+        .collect()
+}
+
+/// The order to write the members of the object or array `text` at `location` in.
+/// None if they are already in order.
+fn member_order(
+    text: &str,
+    members: &[Member],
+    location: &[Step],
+    options: &SortOptions,
+) -> Option<Vec<usize>> {
+    let order: Vec<usize> = if text.starts_with('{') {
+        let mut sorted: Vec<&Member> = members.iter().collect();
+        options
+            .key_order
+            .order_keys(location, &options.collation, &mut sorted);
+        sorted.iter().map(|m| m.index).collect()
+    } else {
+        let values: Vec<Value> = members
+            .iter()
+            .map(|m| serde_json::from_str(&text[m.bytes.clone()]).unwrap_or_default())
+            .collect();
+        let mut sorted = values.clone();
+        sort_array(&mut sorted, options, location);
+
+        // sorting is stable, so equal values keep their order
+        let mut positions: HashMap<String, VecDeque<usize>> = HashMap::new();
+        for (i, v) in values.iter().enumerate() {
+            positions.entry(v.to_string()).or_default().push_back(i);
+        }
+        sorted
+            .iter()
+            .filter_map(|v| positions.get_mut(&v.to_string())?.pop_front())
+            .collect()
+    };
+
+    let unchanged = order.len() != members.len() || order.iter().enumerate().all(|(i, o)| i == *o);
+    (!unchanged).then_some(order)
+}
+
+/// Reorder the members of the object or array `text` at `location`, keeping the text of each member
+/// and the whitespace and commas between them. The output is the same length as `text`.
+fn sort_members(text: &str, location: &[Step], options: &SortOptions) -> String {
+    let members = members(text);
+    let Some(order) = member_order(text, &members, location, options) else {
+        return text.to_string();
+    };
+
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    for (slot, from) in members.iter().zip(order) {
+        output.push_str(&text[last..slot.bytes.start]);
+        output.push_str(&text[members[from].bytes.clone()]);
+        last = slot.bytes.end;
+    }
+    output.push_str(&text[last..]);
+    output
+}
+
+/// Sort only the objects and arrays that contain the changed `lines` of `input`,
+/// keeping the order and formatting of everything else.
+///
+/// Each change is mapped to the innermost object or array containing all of it. Only the members of that
+/// container are reordered, the text of each member is kept as it is, including any objects and arrays inside it.
+pub fn sort_changed(
+    input: &str,
+    lines: &[RangeInclusive<usize>],
+    options: &SortOptions,
+) -> Result<String, JsonError> {
+    if let Err(error) = serde_json::from_str::<Value>(input) {
+        return Err(JsonError::ParseError(ParseError::new(&error, input)));
+    }
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(input.match_indices('\n').map(|(i, _)| i + 1))
+        .filter(|i| *i < input.len())
+        .collect();
+    let containers = containers(input);
+
+    let mut touched: Vec<&Container> = vec![];
+    for span in lines
+        .iter()
+        .filter_map(|l| line_span(input, &line_starts, l))
+    {
+        let innermost = containers
+            .iter()
+            .filter(|c| c.bytes.start <= span.start && span.end <= c.bytes.end)
+            .min_by_key(|c| c.bytes.len());
+        match innermost {
+            Some(c) => touched.push(c),
+            // the change is outside of the root value
+            None => {
+                let full = SortOptions {
+                    changed_lines: None,
+                    ..options.clone()
+                };
+                return sort_json_string(input, &full);
+            }
+        }
+    }
+
+    // innermost first, reordering members keeps the length of a container the same,
+    // so the offsets of the containers around it stay valid
+    touched.sort_by_key(|c| std::cmp::Reverse(c.bytes.start));
+    touched.dedup();
+
+    let mut output = input.to_string();
+    for c in touched {
+        let bytes = c.bytes.clone();
+        let sorted = sort_members(&output[bytes.clone()], &c.location, options);
+        log::debug!("Sorted changed container at bytes {bytes:?}");
+        output.replace_range(bytes, &sorted);
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lines::LineEnding;
+    use crate::order::{JsonPath, KeyOrder, KeyPattern, PinRule};
+
+    fn options() -> SortOptions {
+        SortOptions {
+            line_ending: LineEnding::Lf,
+            use_spaces: true,
+            indents: 2,
+            ..Default::default()
+        }
+    }
+
+    static INPUT: &str = r#"{
+  "z": {
+    "b": 1,
+    "a": [2, 1]
+  },
+  "y": {
+    "d": 1,
+    "c": 2
+  }
+}
+"#;
+
+    #[test]
+    fn finds_containers() {
+        let found = containers(r#"{"a": "[{", "b": [1, {"c": 2}]}"#);
+        let bytes: Vec<Range<usize>> = found.iter().map(|c| c.bytes.clone()).collect();
+        assert_eq!(bytes, vec![0..31, 17..30, 21..29]);
+        assert_eq!(
+            found[2].location,
+            vec![Step::Key("b".into()), Step::Index(1)]
+        );
+    }
+
+    #[test]
+    fn sorts_only_changed_object() {
+        let output = sort_changed(INPUT, &[7..=7], &options()).unwrap();
+
+        assert_eq!(
+            output,
+            "{\n  \"z\": {\n    \"b\": 1,\n    \"a\": [2, 1]\n  },\n  \"y\": {\n    \"c\": 2,\n    \"d\": 1\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn pins_keys_at_container_location() {
+        let options = SortOptions {
+            key_order: KeyOrder::new(vec![PinRule {
+                path: Some(JsonPath::parse("$.y").unwrap()),
+                first: vec![KeyPattern::parse("d").unwrap()],
+                last: vec![],
+            }]),
+            ..options()
+        };
+        let output = sort_changed(INPUT, &[7..=7], &options).unwrap();
+
+        assert_eq!(
+            output,
+            "{\n  \"z\": {\n    \"b\": 1,\n    \"a\": [2, 1]\n  },\n  \"y\": {\n    \"d\": 1,\n    \"c\": 2\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn keeps_nested_containers() {
+        let output = sort_changed(INPUT, &[3..=3], &options()).unwrap();
+
+        assert_eq!(
+            output,
+            "{\n  \"z\": {\n    \"a\": [2, 1],\n    \"b\": 1\n  },\n  \"y\": {\n    \"d\": 1,\n    \"c\": 2\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn change_to_root_object() {
+        let output = sort_changed(INPUT, &[5..=6], &options()).unwrap();
+
+        assert_eq!(
+            output,
+            "{\n  \"y\": {\n    \"d\": 1,\n    \"c\": 2\n  },\n  \"z\": {\n    \"b\": 1,\n    \"a\": [2, 1]\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn changes_in_nested_containers() {
+        let options = SortOptions {
+            sort_arrays: true,
+            ..options()
+        };
+        let input = "{\n  \"b\": [\"y\", \"x\"],\n  \"a\": {\"d\": [\"q\", \"p\"], \"c\": 1}\n}\n";
+        let output = sort_changed(input, &[1..=3], &options).unwrap();
+
+        assert_eq!(
+            output,
+            "{\n  \"a\": {\"d\": [\"q\", \"p\"], \"c\": 1},\n  \"b\": [\"y\", \"x\"]\n}\n"
+        );
+
+        let input = "{\n  \"b\": 1,\n  \"a\": {\n    \"d\": 1,\n    \"c\": 2\n  },\n  \"e\": [\n    \"y\",\n    \"x\"\n  ]\n}\n";
+        let output = sort_changed(input, &[2..=2, 4..=4, 9..=9], &options).unwrap();
+
+        assert_eq!(
+            output,
+            "{\n  \"a\": {\n    \"c\": 2,\n    \"d\": 1\n  },\n  \"b\": 1,\n  \"e\": [\n    \"x\",\n    \"y\"\n  ]\n}\n"
+        );
+    }
+
+    #[test]
+    fn finds_members() {
+        let text = r#"{ "a": [1, {"b": "}"}], "c\"": 2 ,"d":3}"#;
+        let found: Vec<(String, &str)> = members(text)
+            .into_iter()
+            .map(|m| (m.key, &text[m.bytes]))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("a".into(), r#""a": [1, {"b": "}"}]"#),
+                ("c\"".into(), r#""c\"": 2"#),
+                ("d".into(), r#""d":3"#)
+            ]
+        );
+        assert!(members("[ ]").is_empty());
+    }
+
+    #[test]
+    fn no_changes() {
+        assert_eq!(sort_changed(INPUT, &[], &options()).unwrap(), INPUT);
+    }
+}
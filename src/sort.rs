@@ -1,68 +1,212 @@
 use colored::*;
+use ignore::WalkBuilder;
 use regex::Regex;
 use serde::ser::Serialize;
 use serde_json::{Serializer, Value};
+use std::collections::HashSet;
 use std::env;
 use std::error::Error;
 use std::fmt::Display;
 use std::fs::{self};
+use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
 
+use crate::collate::Collation;
+use crate::diff::{diff_path, unified_diff};
+pub use crate::filters::PathFilter;
+use crate::filters::{is_glob, GlobPattern, IGNORE_FILE_NAME};
 pub use crate::formatter::LineFormatter;
 pub use crate::lines::LineEnding;
+use crate::order::{display_location, sort_by_identity, ArrayOrder, KeyOrder, Ordered, Step};
+pub use crate::parse_error::ParseError;
+use crate::partial::sort_changed;
 
 const INVALID_PATH: &str = "INVALID_PATH";
-const IGNORED_FILES: &[&str] = &[
-    "node_modules",
-    "package.json",
-    "package_lock.json",
-    ".DS_Store",
-    "npm-debug.log",
-    ".svn",
-    "CVS",
-    "config.gypi",
-    ".lock-wscript",
-    "package-lock.json",
-    "npm-shrinkwrap.json",
-];
+
+/// Reason files that do not look like JSON are skipped, see [PathFilter::is_json]
+pub const NOT_JSON: &str = "not JSON";
 
 /// Reason why a [Path] could not be JSON sorted
 #[derive(Debug)]
 pub enum JsonError {
     NotFound,
     ReadError,
-    ParseError,
+    ParseError(ParseError),
     WriteError,
+    ConfigError(String),
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonError::NotFound => write!(f, "NotFound"),
+            JsonError::ReadError => write!(f, "ReadError"),
+            JsonError::ParseError(e) => write!(f, "ParseError: {e}"),
+            JsonError::WriteError => write!(f, "WriteError"),
+            JsonError::ConfigError(e) => write!(f, "ConfigError: {e}"),
+        }
+    }
+}
+
+/// Options used to sort a single JSON file
+///
+///  * `line_ending` - type of line ending/seperator to use for newlines
+///  * `use_spaces` - use _spaces_ for whitespace, instead of default _tabs_
+///  * `sort_arrays` - enable to sort arrays. Only sorts arrays containing all string types
+///  * `indents` - number of whitespace indents to use
+///  * `final_newline` - end the file with a line ending
+///  * `changed_lines` - only sort the objects and arrays containing these 1 based line ranges, or the whole file if None
+///  * `key_order` - keys to pin first or last in objects, see [KeyOrder]
+///  * `collation` - how keys and strings in arrays are compared, see [Collation]
+///  * `array_order` - how to sort the arrays at specific paths, see [ArrayOrder]
+///  * `identity_keys` - sort arrays of objects by a member that identifies each object, see [sort_by_identity]
+///
+#[derive(Clone, Debug)]
+pub struct SortOptions {
+    pub line_ending: LineEnding,
+    pub use_spaces: bool,
+    pub sort_arrays: bool,
+    pub indents: usize,
+    pub final_newline: bool,
+    pub changed_lines: Option<Vec<RangeInclusive<usize>>>,
+    pub key_order: KeyOrder,
+    pub collation: Collation,
+    pub array_order: ArrayOrder,
+    pub identity_keys: bool,
+}
+
+impl Default for SortOptions {
+    /// Indent with one tab, keeping the original line ending
+    fn default() -> Self {
+        SortOptions {
+            line_ending: LineEnding::SystemDefault,
+            use_spaces: false,
+            sort_arrays: false,
+            indents: 1,
+            final_newline: true,
+            changed_lines: None,
+            key_order: Default::default(),
+            collation: Default::default(),
+            array_order: Default::default(),
+            identity_keys: false,
+        }
+    }
+}
+
+/// How to sort a single file, resolved by the caller of [sort_files]
+#[derive(Clone, Debug)]
+pub enum FileOptions {
+    Sort(SortOptions),
+    /// Do not sort the file, for this reason
+    Skip(String),
+}
+
+/// What to do with the sorted output of each file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortMode {
+    /// Write the sorted output back to disk
+    Write,
+    /// Only list the files that would be sorted
+    DryRun,
+    /// Compare the sorted output with the file on disk, without modifying it
+    Check,
+    /// Create a unified diff of the sorted output against the file on disk, without modifying it
+    Diff,
 }
 
 /// Result of a sort operation for a JSON file
 ///
 ///  * `path` - [Path] of the file that was sorted
 ///  * `error` - [JsonError] if the sort operation failed
+///  * `unsorted` - file was left unsorted on disk, see [SortMode::Check]
+///  * `changed` - sorted contents were written and differ from the original, see [SortMode::Write]
+///  * `diff` - unified diff of the changes sorting would make, see [SortMode::Diff]
+///  * `skipped` - reason the file was not sorted, e.g. it does not contain JSON, see [PathFilter::is_json]
 ///
 pub struct SortResult {
     path: Box<Path>,
     error: Option<JsonError>,
+    unsorted: bool,
+    changed: bool,
+    diff: Option<String>,
+    skipped: Option<String>,
 }
 
 impl SortResult {
+    pub fn new(path: &Path, error: Option<JsonError>) -> Self {
+        SortResult {
+            path: path.into(),
+            error,
+            unsorted: false,
+            changed: false,
+            diff: None,
+            skipped: None,
+        }
+    }
+
+    pub fn skipped(path: &Path, reason: &str) -> Self {
+        SortResult {
+            skipped: Some(reason.to_string()),
+            ..SortResult::new(path, None)
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     pub fn success(&self) -> bool {
         self.error.is_none()
     }
+
+    pub fn error(&self) -> Option<&JsonError> {
+        self.error.as_ref()
+    }
+
+    pub fn unsorted(&self) -> bool {
+        self.unsorted
+    }
+
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    pub fn diff(&self) -> Option<&str> {
+        self.diff.as_deref()
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        self.skipped.is_some()
+    }
+
+    pub fn skip_reason(&self) -> Option<&str> {
+        self.skipped.as_deref()
+    }
 }
 
 impl Display for SortResult {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let path_str = path_to_relative(&self.path).unwrap_or(INVALID_PATH.into());
 
-        if self.success() {
+        if let Some(reason) = &self.skipped {
+            write!(
+                f,
+                "{} - {}",
+                path_str,
+                format!("Skipped, {reason}").dimmed()
+            )
+        } else if self.unsorted {
+            write!(f, "{} - {}", path_str, "Unsorted".yellow().bold())
+        } else if self.success() {
             write!(f, "{} - {}", path_str, "OK".green().bold())
         } else {
-            let err_msg = format!("{:?}", self.error.as_ref().expect("Not possible"))
-                .red()
-                .bold();
-            write!(f, "{path_str} - {err_msg}")
+            let error = self.error.as_ref().expect("Not possible");
+            let err_msg = error.to_string().red().bold();
+            write!(f, "{path_str} - {err_msg}")?;
+            if let JsonError::ParseError(e) = error {
+                write!(f, "\n{}", e.frame)?;
+            }
+            Ok(())
         }
     }
 }
@@ -72,36 +216,47 @@ impl Display for SortResult {
 /// ## Arguments
 ///
 /// * `files` - a list of relative or absolute Paths to sort
-/// * `line_ending` - type of line ending/seperator to use for newlines
-/// * `use_spaces` - use _spaces_ for whitespace, instead of default _tabs_
-/// * `sort_arrays` - enable to sort arrays. Only sorts arrays containing all string types
-/// * `indents` - number of whitespace indents to use
-/// * `dry_run` - print files that would be sorted, but do not modify
+/// * `filter` - files that should not be modified. See [PathFilter]
+/// * `mode` - write the sorted output, or only report on it. See [SortMode]
+/// * `options_for` - resolve the [SortOptions] to use for each file, or why it should be skipped
 ///
-/// Ignores files that should not be modified. See [IGNORED_FILES]
+/// Directories are walked recursively, skipping any files ignored by `.gitignore` or `.roastignore` files.
+/// Paths that do not exist and contain glob characters are expanded, e.g. `config/**/*.json`.
+/// Files found in directories or by globs are skipped if they do not look like JSON, see [PathFilter::is_json].
+/// Files listed in `files` are sorted whatever they contain, unless [PathFilter::sniffs_listed].
 ///
 #[inline]
-pub fn sort_files(
+pub fn sort_files<F>(
     files: &[PathBuf],
-    line_ending: &LineEnding,
-    use_spaces: bool,
-    sort_arrays: bool,
-    indents: usize,
-    dry_run: bool,
-) -> Vec<SortResult> {
+    filter: &PathFilter,
+    mode: SortMode,
+    mut options_for: F,
+) -> Vec<SortResult>
+where
+    F: FnMut(&Path) -> Result<FileOptions, JsonError>,
+{
     let mut results: Vec<SortResult> = vec![];
 
-    let all_paths = collect_sortables(files);
+    let all_paths = collect_sortables(files, filter);
 
-    for path in all_paths {
-        let res = sort_path(
-            &path,
-            dry_run,
-            line_ending,
-            use_spaces,
-            sort_arrays,
-            indents,
-        );
+    for (path, listed) in all_paths {
+        let sniffed = (!listed || filter.sniffs_listed()) && !filter.has_json_name(&path);
+        if sniffed && path.is_file() && !filter.is_json(&path) {
+            log::debug!("Skipped, not JSON: {:?}", path.to_str());
+            results.push(SortResult::skipped(&path, NOT_JSON));
+            continue;
+        }
+
+        let res = match options_for(&path) {
+            Ok(FileOptions::Sort(options)) => {
+                sort_path(&path, mode, &options).map(|r| skip_unparsed(r, sniffed))
+            }
+            Ok(FileOptions::Skip(reason)) => {
+                log::debug!("Skipped, {reason}: {:?}", path.to_str());
+                Some(SortResult::skipped(&path, &reason))
+            }
+            Err(error) => Some(SortResult::new(&path, Some(error))),
+        };
         if let Some(r) = res {
             results.push(r)
         }
@@ -110,65 +265,83 @@ pub fn sort_files(
     results
 }
 
-fn sort_path(
-    path: &Path,
-    dry_run: bool,
-    line_ending: &LineEnding,
-    use_spaces: bool,
-    sort_arrays: bool,
-    indents: usize,
-) -> Option<SortResult> {
+/// A file that is only sorted because it starts like JSON is skipped if it does not parse,
+/// e.g. a TOML file starting with a `[table]`
+pub fn skip_unparsed(result: SortResult, sniffed: bool) -> SortResult {
+    match result.error() {
+        Some(JsonError::ParseError(_)) if sniffed => {
+            log::debug!("Skipped, not JSON: {:?}", result.path().to_str());
+            SortResult::skipped(result.path(), NOT_JSON)
+        }
+        _ => result,
+    }
+}
+
+fn sort_path(path: &Path, mode: SortMode, options: &SortOptions) -> Option<SortResult> {
     if !path.exists() {
-        return Some(SortResult {
-            path: path.into(),
-            error: Some(JsonError::NotFound),
-        });
+        return Some(SortResult::new(path, Some(JsonError::NotFound)));
     }
 
     let file: String = match read_file(path) {
         Ok(s) => s,
-        Err(e) => {
-            return Some(SortResult {
-                path: path.into(),
-                error: Some(e),
-            })
-        }
-    };
-    let result = match sort_json_string(&file, use_spaces, sort_arrays, line_ending, indents) {
-        Ok(json_string) => {
-            if !dry_run {
-                write_out(path, json_string).err()
-            } else {
-                None
-            }
-        }
-        Err(error) => Some(error),
+        Err(e) => return Some(SortResult::new(path, Some(e))),
     };
 
-    Some(SortResult {
-        path: path.into(),
-        error: result,
-    })
+    Some(sort_contents(path, &file, mode, options, |json_string| {
+        write_out(path, json_string)
+    }))
 }
 
-fn is_ignored(path: &Path) -> bool {
-    if let Ok(full_path) = path.canonicalize() {
-        if let Some(path_str) = full_path.to_str() {
-            return IGNORED_FILES.iter().any(|f| path_str.contains(f));
+/// Sort the `contents` of the file at `path`, and report on the sorted output depending on `mode`.
+///
+/// Used when the contents are not read from `path` on disk, e.g. for a file in the git index.
+/// In [SortMode::Write], the sorted output is saved by calling `write`.
+pub fn sort_contents<W>(
+    path: &Path,
+    contents: &str,
+    mode: SortMode,
+    options: &SortOptions,
+    write: W,
+) -> SortResult
+where
+    W: FnOnce(String) -> Result<(), JsonError>,
+{
+    let json_string = match sort_json_string(contents, options) {
+        Ok(s) => s,
+        Err(error) => return SortResult::new(path, Some(error)),
+    };
+
+    let mut result = SortResult::new(path, None);
+    match mode {
+        SortMode::Write => {
+            let changed = json_string != contents;
+            result.error = write(json_string).err();
+            result.changed = changed && result.success();
+        }
+        SortMode::DryRun => (),
+        SortMode::Check => result.unsorted = json_string != contents,
+        SortMode::Diff => {
+            result.unsorted = json_string != contents;
+            if result.unsorted {
+                result.diff = Some(unified_diff(contents, &json_string, &diff_path(path)));
+            }
         }
     }
 
-    false
+    result
 }
 
 fn path_to_relative(path: &Path) -> Result<String, Box<dyn Error>> {
     let current = env::current_dir()?.canonicalize()?;
 
     if let Ok(full) = path.canonicalize() {
-        if let Some(full_str) = full.to_str() {
-            if let Some(current_str) = current.to_str() {
-                return Ok(format!(".{}", full_str.replace(current_str, "")));
-            }
+        // paths outside the current directory stay absolute
+        let relative = match full.strip_prefix(&current) {
+            Ok(r) => Path::new(".").join(r),
+            Err(_) => full,
+        };
+        if let Some(s) = relative.to_str() {
+            return Ok(s.to_string());
         }
     }
 
@@ -200,8 +373,8 @@ fn read_file(path: &Path) -> Result<String, JsonError> {
     Ok(file)
 }
 
-fn serialize_json(
-    json: &Value,
+fn serialize_json<T: Serialize>(
+    json: &T,
     whitespace_char: char,
     indents: usize,
     line_ending: &LineEnding,
@@ -217,36 +390,78 @@ fn serialize_json(
     Ok(String::from_utf8(buf)?)
 }
 
-fn sort_json_value(head: &mut Value, sort_arrays: bool) -> &mut Value {
-    if !sort_arrays {
+/// Sort the elements of the array at `location`, without sorting any objects or arrays inside it
+pub fn sort_array(list: &mut [Value], options: &SortOptions, location: &[Step]) {
+    if let Some(rule) = options.array_order.rule_at(location) {
+        if rule.sort(list, &options.collation) {
+            log::trace!("Sorted array with strategy {:?}", rule.strategy);
+        } else {
+            log::trace!("Not sorting array with strategy {:?}", rule.strategy);
+        }
+    } else if options.identity_keys && list.iter().all(Value::is_object) {
+        match sort_by_identity(list, &options.collation) {
+            Some(key) => log::debug!(
+                "Sorted array at {} by identity key {key:?}",
+                display_location(location)
+            ),
+            None => log::debug!(
+                "Not sorting array at {}, no identity key",
+                display_location(location)
+            ),
+        }
+    } else if options.sort_arrays {
+        if list.iter().all(|f| f.is_string()) {
+            list.sort_by(|a, b| {
+                options.collation.cmp_strings(
+                    a.as_str().unwrap_or_default(),
+                    b.as_str().unwrap_or_default(),
+                )
+            });
+            log::trace!("Sorted array")
+        } else {
+            log::trace!("Cannot sort array containing non-strings");
+        }
+    }
+}
+
+/// Sort the arrays in `head`, which is the value at `location` in a file.
+/// `location` is only kept up to date while walking the value if any [ArrayOrder] rules need it.
+fn sort_json_value<'a>(
+    head: &'a mut Value,
+    options: &SortOptions,
+    location: &mut Vec<Step>,
+) -> &'a mut Value {
+    let sort_arrays = options.sort_arrays;
+    let by_path = !options.array_order.is_empty() || options.identity_keys;
+    if !sort_arrays && !by_path {
         return head;
     }
 
     match head {
         Value::Array(list) => {
-            if sort_arrays {
-                if list.iter().all(|f| f.is_string()) {
-                    list.sort_by(|a, b| {
-                        a.as_str()
-                            .unwrap_or_default()
-                            .to_lowercase()
-                            .cmp(&b.as_str().unwrap_or_default().to_lowercase())
-                    });
-                    log::trace!("Sorted array")
-                } else {
-                    log::trace!("Cannot sort array containing non-strings");
-                }
-            }
-            for item in list.iter_mut() {
+            sort_array(list, options, location);
+            for (i, item) in list.iter_mut().enumerate() {
                 log::trace!("Sorting inner array of array");
-                sort_json_value(item, sort_arrays);
+                if by_path {
+                    location.push(Step::Index(i));
+                }
+                sort_json_value(item, options, location);
+                if by_path {
+                    location.pop();
+                }
             }
         }
         Value::Object(obj) => {
             log::trace!("Sorting object");
             for (key, val) in obj.iter_mut() {
                 log::trace!("Sorted object value. key: {key}");
-                sort_json_value(val, sort_arrays);
+                if by_path {
+                    location.push(Step::Key(key.clone()));
+                }
+                sort_json_value(val, options, location);
+                if by_path {
+                    location.pop();
+                }
             }
         }
         _ => {
@@ -257,42 +472,57 @@ fn sort_json_value(head: &mut Value, sort_arrays: bool) -> &mut Value {
     head
 }
 
-pub fn sort_json_string(
+pub fn sort_json_string(input: &str, options: &SortOptions) -> Result<String, JsonError> {
+    if let Some(lines) = &options.changed_lines {
+        return sort_changed(input, lines, options);
+    }
+
+    sort_json_string_at(input, options, &[])
+}
+
+/// Sort `input`, which is the value at `location` in a file. Keys are pinned as they would be in the whole file.
+pub fn sort_json_string_at(
     input: &str,
-    use_spaces: bool,
-    sort_arrays: bool,
-    line_ending: &LineEnding,
-    indents: usize,
+    options: &SortOptions,
+    location: &[Step],
 ) -> Result<String, JsonError> {
     let mut json: Value = match serde_json::from_str(input) {
         Ok(v) => v,
         Err(error) => {
             log::debug!("Failed to parse json file. error: {error}");
-            return Err(JsonError::ParseError);
+            return Err(JsonError::ParseError(ParseError::new(&error, input)));
         }
     };
 
-    sort_json_value(&mut json, sort_arrays);
+    sort_json_value(&mut json, options, &mut location.to_vec());
 
-    let desired_line_ending: LineEnding = match line_ending {
+    let desired_line_ending: LineEnding = match options.line_ending {
         // if not specified, use original
         LineEnding::SystemDefault => LineEnding::parse_str(input),
         // else use as configured
-        _ => line_ending.clone(),
+        _ => options.line_ending.clone(),
     };
 
-    let whitespace_char = if use_spaces { ' ' } else { '\t' };
-    let mut json_string =
-        match serialize_json(&json, whitespace_char, indents, &desired_line_ending) {
-            Ok(s) => s,
-            Err(error) => {
-                log::debug!("Serialization error: {error}");
-                return Err(JsonError::WriteError);
-            }
-        };
+    let whitespace_char = if options.use_spaces { ' ' } else { '\t' };
+    let (indents, line_ending) = (options.indents, &desired_line_ending);
+    let serialized = if options.key_order.is_empty() && options.collation.is_byte_order() {
+        serialize_json(&json, whitespace_char, indents, line_ending)
+    } else {
+        let ordered = Ordered::new(&json, &options.key_order, options.collation, location);
+        serialize_json(&ordered, whitespace_char, indents, line_ending)
+    };
+    let mut json_string = match serialized {
+        Ok(s) => s,
+        Err(error) => {
+            log::debug!("Serialization error: {error}");
+            return Err(JsonError::WriteError);
+        }
+    };
 
     // End file with line ending
-    json_string += desired_line_ending.as_str();
+    if options.final_newline {
+        json_string += desired_line_ending.as_str();
+    }
 
     Ok(json_string)
 }
@@ -310,45 +540,89 @@ fn write_out(path: &Path, json_string: String) -> Result<(), JsonError> {
     Ok(())
 }
 
-fn collect_sortables(roots: &[PathBuf]) -> Vec<PathBuf> {
-    let mut results: Vec<PathBuf> = vec![];
+/// Paths to sort from `roots`, with whether each was listed in `roots` rather than found in a directory or by a glob
+fn collect_sortables(roots: &[PathBuf], filter: &PathFilter) -> Vec<(PathBuf, bool)> {
+    let mut results: Vec<(PathBuf, bool)> = vec![];
+    let mut seen: HashSet<PathBuf> = HashSet::new();
 
     for root in roots {
-        if root.is_dir() {
-            for entry in WalkDir::new(root)
-                .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.path().is_file())
-            {
-                let entry_path = entry.path();
-                if is_ignored(entry_path) || path_in_vec(entry_path, &results) {
-                    log::debug!("Ignored: {:?}", entry_path.to_str());
-                    continue;
-                }
-                results.push(entry_path.to_path_buf());
-            }
+        let (paths, listed) = if root.is_dir() {
+            (walk_dir(root, filter, None), false)
+        } else if !root.exists() && is_glob(root) {
+            (expand_glob(root, filter), false)
         } else {
-            if is_ignored(root) || path_in_vec(root, &results) {
-                log::debug!("Ignored: {:?}", root.to_str());
+            (vec![root.to_path_buf()], true)
+        };
+
+        for path in paths {
+            // missing files are never duplicates, so each one is reported
+            if filter.is_excluded(&path) || path.canonicalize().is_ok_and(|full| !seen.insert(full))
+            {
+                log::debug!("Ignored: {:?}", path.to_str());
                 continue;
             }
-            results.push(root.to_path_buf())
+            results.push((path, listed));
         }
     }
 
     results
 }
 
-fn path_in_vec(path: &Path, list: &[PathBuf]) -> bool {
-    list.iter().any(|result| {
-        if !result.exists() || !path.exists() {
-            return false;
+/// Files in `root` and its subdirectories, in a consistent order
+fn walk_dir(root: &Path, filter: &PathFilter, max_depth: Option<usize>) -> Vec<PathBuf> {
+    let dir_filter = filter.clone();
+    WalkBuilder::new(root)
+        .follow_links(true)
+        .hidden(false)
+        .ignore(false)
+        .max_depth(max_depth)
+        .add_custom_ignore_filename(IGNORE_FILE_NAME)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(move |e| {
+            let is_dir = e.file_type().is_some_and(|t| t.is_dir());
+            let excluded = is_dir && e.depth() > 0 && dir_filter.is_excluded_dir(e.path());
+            if excluded {
+                log::debug!("Ignored: {:?}", e.path().to_str());
+            }
+            !excluded
+        })
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Files matching a glob pattern, sorted by path
+fn expand_glob(pattern: &Path, filter: &PathFilter) -> Vec<PathBuf> {
+    let glob = match GlobPattern::new(pattern) {
+        Ok(g) => g,
+        Err(e) => {
+            log::warn!("Invalid glob pattern {:?}: {e}", pattern.to_str());
+            return vec![];
         }
-        let result_str = result.canonicalize().unwrap_or_default();
-        let path_str = path.canonicalize().unwrap_or_default();
-        result_str == path_str
-    })
+    };
+
+    let base = if glob.base.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        &glob.base
+    };
+    let mut paths: Vec<PathBuf> = if base.is_dir() {
+        walk_dir(base, filter, glob.max_depth)
+            .into_iter()
+            .filter(|p| glob.is_match(p))
+            .collect()
+    } else {
+        vec![]
+    };
+    paths.sort();
+
+    if paths.is_empty() {
+        log::warn!("No files match pattern: {:?}", pattern.to_str());
+    }
+
+    paths
 }
 
 #[cfg(test)]
@@ -360,10 +634,25 @@ mod test {
     #[cfg(not(windows))]
     const EOL: &str = "\n";
 
+    fn options(
+        use_spaces: bool,
+        sort_arrays: bool,
+        line_ending: LineEnding,
+        indents: usize,
+    ) -> SortOptions {
+        SortOptions {
+            line_ending,
+            use_spaces,
+            sort_arrays,
+            indents,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn sort_arrays() -> Result<(), String> {
         let input: String = r#"["a", "A", "z", "Z", "m", "M"]"#.into();
-        let result = sort_json_string(&input, true, true, &LineEnding::Lf, 2).unwrap();
+        let result = sort_json_string(&input, &options(true, true, LineEnding::Lf, 2)).unwrap();
 
         let expected: String = r#"[
   "a",
@@ -383,7 +672,7 @@ mod test {
     #[test]
     fn no_sort_arrays() -> Result<(), String> {
         let input: String = r#"["a", "A", "z", "Z", "m", "M"]"#.into();
-        let result = sort_json_string(&input, true, false, &LineEnding::Lf, 2).unwrap();
+        let result = sort_json_string(&input, &options(true, false, LineEnding::Lf, 2)).unwrap();
 
         let expected: String = r#"[
   "a",
@@ -414,7 +703,7 @@ mod test {
         }"#
         .into();
 
-        let result = sort_json_string(&input, true, true, &LineEnding::Lf, 2).unwrap();
+        let result = sort_json_string(&input, &options(true, true, LineEnding::Lf, 2)).unwrap();
 
         let expected: String = r#"{
   "a": {
@@ -451,7 +740,7 @@ mod test {
         }"#
         .into();
 
-        let result = sort_json_string(&input, true, false, &LineEnding::Lf, 2).unwrap();
+        let result = sort_json_string(&input, &options(true, false, LineEnding::Lf, 2)).unwrap();
 
         let expected: String = r#"{
   "a": {
@@ -483,7 +772,7 @@ mod test {
 "#
         .into();
 
-        let result = sort_json_string(&input, true, false, &LineEnding::Lf, 3).unwrap();
+        let result = sort_json_string(&input, &options(true, false, LineEnding::Lf, 3)).unwrap();
 
         let expected: String = r#"{
    "a": 2,
@@ -505,7 +794,7 @@ mod test {
 "#
         .into();
 
-        let result = sort_json_string(&input, false, false, &LineEnding::Lf, 3).unwrap();
+        let result = sort_json_string(&input, &options(false, false, LineEnding::Lf, 3)).unwrap();
 
         let expected: String = "{
 \t\t\t\"a\": 2,
@@ -522,7 +811,7 @@ mod test {
     fn indentation_3_tabs_array() -> Result<(), String> {
         let input: String = "[\n  \"z\",\n  \"a\"\n]".into();
 
-        let result = sort_json_string(&input, false, true, &LineEnding::Lf, 3).unwrap();
+        let result = sort_json_string(&input, &options(false, true, LineEnding::Lf, 3)).unwrap();
 
         let expected: String = "[
 \t\t\t\"a\",
@@ -537,9 +826,12 @@ mod test {
 
     #[test]
     fn line_endings_system() -> Result<(), String> {
-        let input: String = format!(r#"[{EOL}  {{{EOL}    "a": "y",{EOL}    "b": "b"{EOL}  }},{EOL}  {{{EOL}    "c": "r",{EOL}    "p": "d"{EOL}  }}{EOL}]{EOL}"#);
+        let input: String = format!(
+            r#"[{EOL}  {{{EOL}    "a": "y",{EOL}    "b": "b"{EOL}  }},{EOL}  {{{EOL}    "c": "r",{EOL}    "p": "d"{EOL}  }}{EOL}]{EOL}"#
+        );
 
-        let result = sort_json_string(&input, true, false, &LineEnding::SystemDefault, 2).unwrap();
+        let result =
+            sort_json_string(&input, &options(true, false, LineEnding::SystemDefault, 2)).unwrap();
 
         assert_eq!(result, input);
         Ok(())
@@ -547,9 +839,13 @@ mod test {
 
     #[test]
     fn line_endings_preseve_original_crlf() -> Result<(), String> {
-        let input: String = format!(r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#, "\r\n");
+        let input: String = format!(
+            r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#,
+            "\r\n"
+        );
 
-        let result = sort_json_string(&input, true, false, &LineEnding::SystemDefault, 2).unwrap();
+        let result =
+            sort_json_string(&input, &options(true, false, LineEnding::SystemDefault, 2)).unwrap();
 
         assert_eq!(result, input);
         Ok(())
@@ -557,9 +853,13 @@ mod test {
 
     #[test]
     fn line_endings_preseve_original_lf() -> Result<(), String> {
-        let input: String = format!(r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#, "\n");
+        let input: String = format!(
+            r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#,
+            "\n"
+        );
 
-        let result = sort_json_string(&input, true, false, &LineEnding::SystemDefault, 2).unwrap();
+        let result =
+            sort_json_string(&input, &options(true, false, LineEnding::SystemDefault, 2)).unwrap();
 
         assert_eq!(result, input);
         Ok(())
@@ -567,9 +867,12 @@ mod test {
 
     #[test]
     fn line_endings_crlf_in_cr_out() -> Result<(), String> {
-        let input: String = format!(r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#, "\r\n");
+        let input: String = format!(
+            r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#,
+            "\r\n"
+        );
 
-        let result = sort_json_string(&input, true, false, &LineEnding::Cr, 2).unwrap();
+        let result = sort_json_string(&input, &options(true, false, LineEnding::Cr, 2)).unwrap();
 
         assert_eq!(result, input.replace("\r\n", "\r"));
         Ok(())
@@ -577,9 +880,12 @@ mod test {
 
     #[test]
     fn line_endings_crlf_in_lf_out() -> Result<(), String> {
-        let input: String = format!(r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#, "\r\n");
+        let input: String = format!(
+            r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#,
+            "\r\n"
+        );
 
-        let result = sort_json_string(&input, true, false, &LineEnding::Lf, 2).unwrap();
+        let result = sort_json_string(&input, &options(true, false, LineEnding::Lf, 2)).unwrap();
 
         assert_eq!(result, input.replace("\r\n", "\n"));
         Ok(())
@@ -587,14 +893,29 @@ mod test {
 
     #[test]
     fn line_endings_lf_in_crlf_out() -> Result<(), String> {
-        let input: String = format!(r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#, "\n");
+        let input: String = format!(
+            r#"[{0}  {{{0}    "a": "y",{0}    "b": "b"{0}  }},{0}  {{{0}    "c": "r",{0}    "p": "d"{0}  }}{0}]{0}"#,
+            "\n"
+        );
 
-        let result = sort_json_string(&input, true, false, &LineEnding::CrLf, 2).unwrap();
+        let result = sort_json_string(&input, &options(true, false, LineEnding::CrLf, 2)).unwrap();
 
         assert_eq!(result, input.replace("\n", "\r\n"));
         Ok(())
     }
 
+    #[test]
+    fn no_final_newline() -> Result<(), String> {
+        let input: String = r#"{"b": 1, "a": 2}"#.into();
+        let mut options = options(true, false, LineEnding::Lf, 2);
+        options.final_newline = false;
+
+        let result = sort_json_string(&input, &options).unwrap();
+
+        assert_eq!(result, "{\n  \"a\": 2,\n  \"b\": 1\n}");
+        Ok(())
+    }
+
     #[test]
     fn large_complex() -> Result<(), String> {
         let minified: String = r#"{"root":true,"env":{"es6":true,"node":true},"extends":["eslint:recommended","plugin:prettier/recommended"],"parserOptions":{"ecmaVersion":2018,"sourceType":"module"},"plugins":["ava","scanjs-rules","no-unsanitized","import"],"rules":{"ava/assertion-arguments":"error","ava/max-asserts":["off",5],"ava/no-async-fn-without-await":"error","ava/no-cb-test":"off","ava/no-duplicate-modifiers":"error","ava/no-identical-title":"error","ava/no-invalid-end":"error","ava/no-nested-tests":"error","ava/no-only-test":"error","ava/no-skip-assert":"error","ava/no-skip-test":"error","ava/no-statement-after-end":"error","ava/no-todo-implementation":"error","ava/no-todo-test":"warn","ava/no-unknown-modifiers":"error","ava/prefer-async-await":"error","ava/prefer-power-assert":"off","ava/test-ended":"error","ava/test-title":["error","if-multiple"],"ava/use-t":"error","ava/use-t-well":"error","ava/use-test":"error","ava/use-true-false":"error","curly":"error","import/no-extraneous-dependencies":["error",{"devDependencies":["**/*test.js","test/**/*.*","rollup.config.js"]}],"no-constant-condition":["error",{"checkLoops":false}],"no-console":"off","no-else-return":"error","no-inner-declarations":"error","no-unneeded-ternary":"error","no-useless-return":"error","no-var":"error","one-var":["error","never"],"prefer-arrow-callback":"error","prefer-const":"error","prefer-template":"error","strict":"error","scanjs-rules/accidental_assignment":1,"scanjs-rules/assign_to_hostname":1,"scanjs-rules/assign_to_href":1,"scanjs-rules/assign_to_location":1,"scanjs-rules/assign_to_onmessage":1,"scanjs-rules/assign_to_pathname":1,"scanjs-rules/assign_to_protocol":1,"scanjs-rules/assign_to_search":1,"scanjs-rules/assign_to_src":1,"scanjs-rules/call_Function":1,"scanjs-rules/call_addEventListener":1,"scanjs-rules/call_addEventListener_deviceproximity":1,"scanjs-rules/call_addEventListener_message":1,"scanjs-rules/call_connect":1,"scanjs-rules/call_eval":1,"scanjs-rules/call_execScript":1,"scanjs-rules/call_hide":1,"scanjs-rules/call_open_remote=true":1,"scanjs-rules/call_parseFromString":1,"scanjs-rules/call_setImmediate":1,"scanjs-rules/call_setInterval":1,"scanjs-rules/call_setTimeout":1,"scanjs-rules/identifier_indexedDB":1,"scanjs-rules/identifier_localStorage":1,"scanjs-rules/identifier_sessionStorage":1,"scanjs-rules/new_Function":1,"scanjs-rules/property_addIdleObserver":1,"scanjs-rules/property_createContextualFragment":1,"scanjs-rules/property_crypto":1,"scanjs-rules/property_geolocation":1,"scanjs-rules/property_getUserMedia":1,"scanjs-rules/property_indexedDB":1,"scanjs-rules/property_localStorage":1,"scanjs-rules/property_mgmt":1,"scanjs-rules/property_sessionStorage":1,"symbol-description":"error","yoda":["error","never",{"exceptRange":true}]}}"#.into();
@@ -726,7 +1047,8 @@ mod test {
 \t}
 }\n";
 
-        let result = sort_json_string(&minified, false, false, &LineEnding::Lf, 1).unwrap();
+        let result =
+            sort_json_string(&minified, &options(false, false, LineEnding::Lf, 1)).unwrap();
 
         assert_eq!(result, prettified);
         Ok(())
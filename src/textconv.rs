@@ -0,0 +1,62 @@
+use std::path::PathBuf;
+
+use crate::config::PartialOptions;
+use crate::git::install_driver;
+use crate::lines::LineEnding;
+use crate::sort::sort_json_string;
+
+/// Name of the diff driver in git config and `.gitattributes`
+const DRIVER_NAME: &str = "roast";
+
+/// Canonical rendering of `input` for git to diff, sorted and pretty printed with `\n` line endings.
+///
+/// Only the sorting and indentation options set in `cli` are used, so that every version of a file is
+/// rendered the same way whatever its formatting on disk. Input that is not JSON is returned unchanged.
+pub fn textconv(input: &str, cli: &PartialOptions) -> String {
+    let options = PartialOptions {
+        line_ending: Some(LineEnding::Lf),
+        final_newline: Some(true),
+        ..cli.clone()
+    }
+    .to_sort_options();
+
+    match sort_json_string(input, &options) {
+        Ok(s) => s,
+        Err(e) => {
+            log::debug!("Not converting input: {e}");
+            input.to_string()
+        }
+    }
+}
+
+/// Adds the textconv command to the local git config, and a `*.json` pattern to the `.gitattributes` file
+/// in the root of the work tree if it does not already contain one. Returns the path of the `.gitattributes` file.
+pub fn install() -> Result<PathBuf, git2::Error> {
+    install_driver(
+        &format!("diff.{DRIVER_NAME}.textconv"),
+        "roast textconv",
+        &format!("diff={DRIVER_NAME}"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn canonical_rendering() {
+        let cli = PartialOptions::default();
+        let minified = "{\"b\":{\"d\":1,\"c\":[2,1]},\"a\":0}";
+        let pretty = "{\r\n    \"a\": 0,\r\n    \"b\": {\"c\": [2, 1], \"d\": 1}\r\n}";
+
+        let expected = "{\n\t\"a\": 0,\n\t\"b\": {\n\t\t\"c\": [\n\t\t\t2,\n\t\t\t1\n\t\t],\n\t\t\"d\": 1\n\t}\n}\n";
+        assert_eq!(textconv(minified, &cli), expected);
+        assert_eq!(textconv(pretty, &cli), expected);
+    }
+
+    #[test]
+    fn not_json_unchanged() {
+        let input = "not { json";
+        assert_eq!(textconv(input, &PartialOptions::default()), input);
+    }
+}
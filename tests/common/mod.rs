@@ -0,0 +1,94 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use git2::Repository;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+pub static UNSORTED: &str = "{\"b\": 1, \"a\": 0}";
+/// [UNSORTED] as roast writes it with the default options
+pub static SORTED: &str = "{\n\t\"a\": 0,\n\t\"b\": 1\n}\n";
+
+/// Write `contents` to `name` in `dir`, creating any missing parent directories
+pub fn write(dir: &Path, name: &str, contents: &str) {
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+pub fn read(dir: &Path, name: &str) -> String {
+    fs::read_to_string(dir.join(name)).unwrap()
+}
+
+/// Assert that the file written with [UNSORTED] was sorted
+#[track_caller]
+pub fn assert_sorted(dir: &Path, name: &str) {
+    assert_eq!(read(dir, name), SORTED, "{name} was not sorted");
+}
+
+/// Assert that the file written with [UNSORTED] was left alone
+#[track_caller]
+pub fn assert_unsorted(dir: &Path, name: &str) {
+    assert_eq!(read(dir, name), UNSORTED, "{name} was changed");
+}
+
+pub fn stage(repo: &Repository, name: &str) {
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(name)).unwrap();
+    index.write().unwrap();
+}
+
+/// Commit every file in the work tree of `repo`
+pub fn commit(repo: &Repository, message: &str) {
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let signature = git2::Signature::now("roast", "roast@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+    .unwrap();
+}
+
+/// Contents of `name` in the index of the repository at `dir`
+pub fn staged_contents(dir: &Path, name: &str) -> String {
+    let repo = Repository::open(dir).unwrap();
+    let index = repo.index().unwrap();
+    let entry = index.get_path(Path::new(name), 0).unwrap();
+    let blob = repo.find_blob(entry.id).unwrap();
+    String::from_utf8(blob.content().to_vec()).unwrap()
+}
+
+/// Run the git command line in `dir` with `args`, with an identity to commit with.
+/// The directory of the roast binary is added to `PATH`, so hooks can run it.
+pub fn git(dir: &Path, args: &[&str]) -> Output {
+    let roast = assert_cmd::cargo::cargo_bin("roast");
+    let mut path = vec![roast.parent().unwrap().to_path_buf()];
+    path.extend(env::split_paths(&env::var_os("PATH").unwrap_or_default()));
+
+    Command::new("git")
+        .current_dir(dir)
+        .args([
+            "-c",
+            "user.name=roast",
+            "-c",
+            "user.email=roast@example.com",
+        ])
+        .args(args)
+        .env("PATH", env::join_paths(path).unwrap())
+        .env_remove("GIT_INDEX_FILE")
+        .output()
+        .unwrap()
+}
@@ -0,0 +1,99 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use std::fs;
+
+static CONFIG: &str = r#"
+spaces = true
+arrays = true
+
+[[array]]
+path = "/dependencies/*/features"
+strategy = "scalars"
+
+[[array]]
+path = "$.users"
+strategy = "objects"
+by = ["team", "id"]
+
+[[array]]
+path = "$.point"
+strategy = "none"
+"#;
+
+#[test]
+fn array_strategies_from_config() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("roast.toml"), CONFIG).unwrap();
+    fs::write(
+        tempdir.path().join("a.json"),
+        r#"{
+            "dependencies": {"serde": {"features": ["std", "derive", 2, 1]}},
+            "users": [{"id": 2, "team": "b"}, {"id": 3}, {"id": 1, "team": "b"}, {"id": 9, "team": "a"}],
+            "point": ["y", "x"],
+            "tags": ["b", "a"]
+        }"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--lineEnding")
+        .arg("lf")
+        .arg("a.json")
+        .assert()
+        .success();
+
+    let sorted = fs::read_to_string(tempdir.path().join("a.json")).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&sorted).unwrap();
+    assert_eq!(
+        value["dependencies"]["serde"]["features"],
+        serde_json::json!([1, 2, "derive", "std"])
+    );
+    let ids: Vec<u64> = value["users"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|u| u["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, vec![9, 1, 2, 3]);
+    assert_eq!(value["point"], serde_json::json!(["y", "x"]));
+    assert_eq!(value["tags"], serde_json::json!(["a", "b"]));
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("print-config")
+        .arg("a.json")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_contains!(
+        stdout,
+        "[[array]]\npath = \"$.users\"\nstrategy = \"objects\"\nby = [\"team\", \"id\"]\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn objects_strategy_requires_keys() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(
+        tempdir.path().join("roast.toml"),
+        "[[array]]\npath = \"$.users\"\nstrategy = \"objects\"\n",
+    )
+    .unwrap();
+    fs::write(tempdir.path().join("a.json"), "{}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("a.json")
+        .assert()
+        .code(9);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "array strategy for $.users is missing `by`");
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn check_unsorted() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let mut path = PathBuf::from(tempdir.path());
+    path.push("unsorted.json");
+    let original = "{\n  \"b\": 1,\n  \"a\": 0\n}\n";
+    fs::write(&path, original).unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--check")
+        .arg("--spaces")
+        .arg(&path)
+        .assert()
+        .code(3);
+
+    let out = res.get_output();
+    let stderr = String::from_utf8(out.stderr.clone()).unwrap();
+
+    assert_contains!(stderr, "unsorted.json - Unsorted");
+    assert_contains!(stderr, "1 files are not sorted");
+    assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn check_sorted() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let mut path = PathBuf::from(tempdir.path());
+    path.push("sorted.json");
+    fs::write(&path, "{\n  \"a\": 0,\n  \"b\": 1\n}\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--check")
+        .arg("--spaces")
+        .arg(&path)
+        .assert()
+        .success();
+
+    let out = res.get_output();
+    let stderr = String::from_utf8(out.stderr.clone()).unwrap();
+
+    assert_contains!(stderr, "sorted.json - OK");
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn check_stdin() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .write_stdin("{\"b\": 1, \"a\": 0}")
+        .arg("--check")
+        .assert()
+        .code(3);
+
+    assert!(res.get_output().stdout.is_empty());
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.write_stdin("{\n\t\"a\": 0\n}\n")
+        .arg("--check")
+        .assert()
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use std::fs;
+
+#[test]
+fn unicode_from_config() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(
+        tempdir.path().join("roast.toml"),
+        "collation = \"unicode\"\n",
+    )
+    .unwrap();
+    fs::write(
+        tempdir.path().join("fr.json"),
+        r#"{"zone": 1, "été": 1, "Ärger": 1, "etage": 1, "Zebra": 1, "apple": 1}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--lineEnding")
+        .arg("lf")
+        .arg("fr.json")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("fr.json")).unwrap(),
+        "{\n\t\"apple\": 1,\n\t\"Ärger\": 1,\n\t\"etage\": 1,\n\t\"été\": 1,\n\t\"Zebra\": 1,\n\t\"zone\": 1\n}\n"
+    );
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("print-config")
+        .arg("fr.json")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_contains!(stdout, "collation = \"unicode\"\n");
+
+    Ok(())
+}
+
+#[test]
+fn ignore_case_arrays() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--collation")
+        .arg("ignore-case")
+        .arg("--arrays")
+        .arg("--lineEnding")
+        .arg("lf")
+        .write_stdin(r#"{"b": ["b", "a", "B", "A"], "B": 1}"#)
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_eq!(
+        stdout,
+        "{\n\t\"B\": 1,\n\t\"b\": [\n\t\t\"A\",\n\t\t\"a\",\n\t\t\"B\",\n\t\t\"b\"\n\t]\n}\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn invalid_collation() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(
+        tempdir.path().join("roast.toml"),
+        "collation = \"klingon\"\n",
+    )
+    .unwrap();
+    fs::write(tempdir.path().join("a.json"), "{}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("a.json")
+        .assert()
+        .code(9);
+
+    Ok(())
+}
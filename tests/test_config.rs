@@ -0,0 +1,141 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use std::fs;
+
+static CONFIG: &str = r#"
+spaces = true
+
+[[overrides]]
+files = ["i18n/**"]
+arrays = true
+
+[[overrides]]
+files = "*.schema.json"
+indentationCount = 4
+"#;
+
+#[test]
+fn config_file_overrides() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("roast.toml"), CONFIG).unwrap();
+    fs::create_dir_all(tempdir.path().join("i18n")).unwrap();
+    fs::write(
+        tempdir.path().join("i18n/en.json"),
+        r#"{"b": ["z", "a"], "a": 1}"#,
+    )
+    .unwrap();
+    fs::write(
+        tempdir.path().join("x.schema.json"),
+        r#"{"b": ["z", "a"], "a": 1}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--lineEnding")
+        .arg("lf")
+        .arg(".")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("i18n/en.json")).unwrap(),
+        "{\n  \"a\": 1,\n  \"b\": [\n    \"a\",\n    \"z\"\n  ]\n}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("x.schema.json")).unwrap(),
+        "{\n    \"a\": 1,\n    \"b\": [\n        \"z\",\n        \"a\"\n    ]\n}\n"
+    );
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn print_config() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("roast.toml"), CONFIG).unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("print-config")
+        .arg("i18n/de.json")
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_contains!(stdout, "# overrides: i18n/**\n");
+    assert_contains!(
+        stdout,
+        "arrays = true\nindentationCount = 2\nlineEnding = \"original\"\nspaces = true\nfinalNewline = true\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn invalid_config() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join(".roastrc"), r#"{"spacs": true}"#).unwrap();
+    fs::write(tempdir.path().join("a.json"), "{}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("a.json")
+        .assert()
+        .code(9);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "unknown field `spacs`");
+
+    Ok(())
+}
+
+#[test]
+fn invalid_line_ending() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(
+        tempdir.path().join("roast.toml"),
+        "lineEnding = \"bogus\"\n",
+    )
+    .unwrap();
+    fs::write(tempdir.path().join("a.json"), "{}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("a.json")
+        .assert()
+        .code(9);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "unknown variant `bogus`");
+
+    Ok(())
+}
+
+#[test]
+fn editorconfig() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(
+        tempdir.path().join(".editorconfig"),
+        "root = true\n\n[*.json]\nindent_style = space\nindent_size = 3\nend_of_line = crlf\n",
+    )
+    .unwrap();
+    fs::write(tempdir.path().join("a.json"), "{\"b\": 1, \"a\": 0}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("a.json")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("a.json")).unwrap(),
+        "{\r\n   \"a\": 0,\r\n   \"b\": 1\r\n}\r\n"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use std::fs;
+
+#[test]
+fn diff_file() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let original = "{\n  \"b\": 1,\n  \"a\": 0\n}\n";
+    fs::write(tempdir.path().join("unsorted.json"), original).unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("--diff")
+        .arg("--spaces")
+        .arg("unsorted.json")
+        .assert()
+        .success();
+
+    let out = res.get_output();
+    let stdout = String::from_utf8(out.stdout.clone()).unwrap();
+    let stderr = String::from_utf8(out.stderr.clone()).unwrap();
+
+    let expected = "--- a/unsorted.json
++++ b/unsorted.json
+@@ -1,4 +1,4 @@
+ {
+-  \"b\": 1,
+-  \"a\": 0
++  \"a\": 0,
++  \"b\": 1
+ }
+";
+    assert_eq!(stdout, expected);
+    assert_contains!(stderr, "unsorted.json - Unsorted");
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("unsorted.json")).unwrap(),
+        original
+    );
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn diff_sorted_file_is_empty() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("sorted.json"), "{\n  \"a\": 0\n}\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("--diff")
+        .arg("--spaces")
+        .arg("sorted.json")
+        .assert()
+        .success();
+
+    assert!(res.get_output().stdout.is_empty());
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn diff_stdin() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .write_stdin("{\"b\": 1, \"a\": 0}\n")
+        .arg("--diff")
+        .arg("--lineEnding")
+        .arg("lf")
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_contains!(stdout, "--- a/stdin\n+++ b/stdin\n");
+    assert_contains!(stdout, "-{\"b\": 1, \"a\": 0}\n+{\n+\t\"a\": 0,\n");
+
+    Ok(())
+}
+
+#[test]
+fn diff_absolute_path() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let cwd = tempfile::tempdir().unwrap();
+    let file = tempdir.path().canonicalize().unwrap().join("x.json");
+    fs::write(&file, "{\"b\": 1, \"a\": 0}\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(cwd.path())
+        .arg("--diff")
+        .arg(&file)
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    let relative = file.to_str().unwrap().trim_start_matches('/');
+    assert_contains!(stdout, &format!("--- a/{relative}\n+++ b/{relative}\n"));
+    assert!(!stdout.contains("a//"));
+
+    Ok(())
+}
+
+#[test]
+fn diff_from_repo_subdirectory() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    git2::Repository::init(tempdir.path()).unwrap();
+    let sub = tempdir.path().join("sub");
+    fs::create_dir(&sub).unwrap();
+    fs::write(sub.join("x.json"), "{\"b\": 1, \"a\": 0}\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(&sub)
+        .arg("--diff")
+        .arg("x.json")
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_contains!(stdout, "--- a/sub/x.json\n+++ b/sub/x.json\n");
+
+    Ok(())
+}
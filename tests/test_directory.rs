@@ -23,18 +23,33 @@ fn default_sort_directory() -> Result<(), String> {
         fs::write(path_s, TEST_FILES[i]).unwrap();
     }
 
-
     let mut cmd = Command::cargo_bin("roast").unwrap();
-    let res = cmd.arg(tempdir.path()).arg("--spaces").assert().success();
+    // broken fixtures cause a parse error exit code
+    let res = cmd.arg(tempdir.path()).arg("--spaces").assert().code(6);
 
     let out = res.get_output();
     let stderr = String::from_utf8(out.stderr.clone()).unwrap();
 
+    assert_contains!(
+        stderr,
+        "5 files sorted\n2 files could not be sorted\n1 files skipped, not JSON"
+    );
+
+    // files that are not JSON are only listed with --verbose
+    assert!(!stderr.contains("not_json.yml"));
+
     for (i, f) in TEST_FILE_PATHS.iter().enumerate() {
-        let status_line = match f.contains("not_json") {
-            true => format!("{} - ParseError", f.replace("/", format!("{}", std::path::MAIN_SEPARATOR).as_str())),
-            false => format!("{} - OK", f.replace("/", format!("{}", std::path::MAIN_SEPARATOR).as_str())),
+        let status = if f.ends_with(".yml") {
+            continue;
+        } else if f.contains("not_json") {
+            "ParseError"
+        } else {
+            "OK"
         };
+        let status_line = format!(
+            "{} - {status}",
+            f.replace("/", format!("{}", std::path::MAIN_SEPARATOR).as_str())
+        );
         assert_contains!(stderr, &status_line);
 
         let mut t = temp_path.clone();
@@ -48,6 +63,85 @@ fn default_sort_directory() -> Result<(), String> {
     Ok(())
 }
 
+#[test]
+fn extra_extensions() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("a.geojson"), "\"not an object\"").unwrap();
+    fs::write(tempdir.path().join("b.txt"), "\"not an object\"").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg(".")
+        .arg("--ext")
+        .arg("geojson")
+        .arg("--verbose")
+        .assert()
+        .success();
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "a.geojson - OK");
+    assert_contains!(stderr, "b.txt - Skipped, not JSON");
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn toml_is_not_json() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(
+        tempdir.path().join("Cargo.toml"),
+        "[package]\nname = \"a\"\n",
+    )
+    .unwrap();
+    fs::write(tempdir.path().join("a.json"), "{}\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("--check")
+        .arg(".")
+        .assert()
+        .success();
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert!(!stderr.contains("Cargo.toml"));
+    assert_contains!(stderr, "1 files skipped, not JSON");
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn named_files_are_sorted_whatever_they_contain() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("config"), "{\"a\": 1,}").unwrap();
+    fs::write(tempdir.path().join("list"), "\"not an object\"").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("config")
+        .arg("list")
+        .assert()
+        .code(6);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "config - ");
+    assert_contains!(stderr, "list - OK");
+
+    // found in a directory, the same files are skipped
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd.current_dir(tempdir.path()).arg(".").assert().success();
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "don't lead to any json files");
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
 static TEST_FILE_PATHS: &[&str] = &[
     "test1/file1.json",
     "test1/.sneakyrc",
@@ -56,7 +150,7 @@ static TEST_FILE_PATHS: &[&str] = &[
     "file5.json",
     "not_json_broken.json",
     "not_json_liar.json",
-    "not_json.yml"
+    "not_json.yml",
 ];
 
 static TEST_FILES: &[&str] = &[
@@ -0,0 +1,109 @@
+use assert_cmd::Command;
+use std::fs;
+
+#[test]
+fn not_found() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg(tempdir.path().join("missing.json"))
+        .assert()
+        .code(4);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg(tempdir.path().join("missing.json"))
+        .arg("--failOn")
+        .arg("parse")
+        .assert()
+        .success();
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn parse_error() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("broken.json");
+    fs::write(&path, "{\"a\": 1,}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg(&path).assert().code(6);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg(&path)
+        .arg("--failOn")
+        .arg("none")
+        .assert()
+        .success();
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn highest_code_used() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("broken.json");
+    fs::write(&path, "{\"a\": 1,}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg(&path)
+        .arg(tempdir.path().join("missing.json"))
+        .assert()
+        .code(6);
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn no_inputs() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg(tempdir.path()).assert().success();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg(tempdir.path())
+        .arg("--failOn")
+        .arg("parse,missing,empty")
+        .assert()
+        .code(8);
+
+    tempdir.close().unwrap();
+    Ok(())
+}
+
+#[test]
+fn stdin_parse_error() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.write_stdin("asdf").assert().code(6);
+
+    Ok(())
+}
+
+#[test]
+fn stdin_fail_on() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.write_stdin("{\"a\": 1,}").assert().code(6);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .write_stdin("{\"a\": 1,}")
+        .arg("--failOn")
+        .arg("none")
+        .assert()
+        .success();
+    assert_eq!(res.get_output().stdout, b"{\"a\": 1,}");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.write_stdin("{\"a\": 1,}")
+        .arg("--check")
+        .arg("--failOn")
+        .arg("missing")
+        .assert()
+        .success();
+
+    Ok(())
+}
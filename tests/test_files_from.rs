@@ -0,0 +1,94 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+
+mod common;
+use common::{assert_sorted, assert_unsorted, write, UNSORTED};
+
+#[test]
+fn newline_separated_stdin() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "a.json", UNSORTED);
+    write(tempdir.path(), "b c.json", UNSORTED);
+    write(tempdir.path(), "d.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--filesFrom")
+        .arg("-")
+        .write_stdin("a.json\r\nb c.json\n\n")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "a.json");
+    assert_sorted(tempdir.path(), "b c.json");
+    assert_unsorted(tempdir.path(), "d.json");
+
+    Ok(())
+}
+
+#[test]
+fn nul_separated_file() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "a\nb.json", UNSORTED);
+    write(tempdir.path(), "c.json", UNSORTED);
+    write(tempdir.path(), "d.json", UNSORTED);
+    write(tempdir.path(), "list", "a\nb.json\0c.json\0");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--files-from")
+        .arg("list")
+        .arg("-0")
+        .arg("d.json")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "a\nb.json");
+    assert_sorted(tempdir.path(), "c.json");
+    assert_sorted(tempdir.path(), "d.json");
+
+    Ok(())
+}
+
+#[test]
+fn duplicate_paths() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "a.json", UNSORTED);
+    write(tempdir.path(), "sub/b.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("--filesFrom")
+        .arg("-")
+        .write_stdin("a.json\n./a.json\nsub/../a.json\nsub/b.json\n")
+        .assert()
+        .success();
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_eq!(stderr.matches("a.json - ").count(), 1);
+    assert_contains!(stderr, "2 files sorted");
+    assert_sorted(tempdir.path(), "a.json");
+    assert_sorted(tempdir.path(), "sub/b.json");
+
+    Ok(())
+}
+
+#[test]
+fn missing_list() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd.arg("--filesFrom").arg("no_such_list").assert().code(5);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "Error reading file list");
+
+    Ok(())
+}
+
+#[test]
+fn null_requires_files_from() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg("-0").arg("a.json").assert().code(2);
+
+    Ok(())
+}
@@ -0,0 +1,549 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use git2::Repository;
+use std::fs;
+use std::path::Path;
+
+mod common;
+use common::{
+    assert_sorted, assert_unsorted, commit, git, read, stage, staged_contents, write, SORTED,
+    UNSORTED,
+};
+
+#[test]
+fn staged() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "staged.json", UNSORTED);
+    write(tempdir.path(), "partial.json", UNSORTED);
+    write(tempdir.path(), "unstaged.json", UNSORTED);
+    stage(&repo, "staged.json");
+    stage(&repo, "partial.json");
+    let partial_edit = "{\"c\": 2, \"b\": 1, \"a\": 0}";
+    write(tempdir.path(), "partial.json", partial_edit);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--staged")
+        .arg("--lineEnding")
+        .arg("lf")
+        .assert()
+        .success();
+
+    assert_eq!(staged_contents(tempdir.path(), "staged.json"), SORTED);
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("staged.json")).unwrap(),
+        SORTED
+    );
+
+    // unstaged edits are kept
+    assert_eq!(staged_contents(tempdir.path(), "partial.json"), SORTED);
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("partial.json")).unwrap(),
+        partial_edit
+    );
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("unstaged.json")).unwrap(),
+        UNSORTED
+    );
+
+    let statuses = repo.statuses(None).unwrap();
+    let status = statuses
+        .iter()
+        .find(|s| s.path() == Some("staged.json"))
+        .unwrap();
+    assert!(!status.status().is_wt_modified());
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn staged_in_commit_all() -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "a.json", SORTED);
+    commit(&repo, "initial");
+
+    // `git commit -a` stages into a temporary index, which the hook has to use
+    let hook = tempdir.path().join(".git/hooks/pre-commit");
+    write(
+        tempdir.path(),
+        ".git/hooks/pre-commit",
+        "#!/bin/sh\nexec roast --staged --check\n",
+    );
+    fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+    write(tempdir.path(), "a.json", UNSORTED);
+
+    let output = git(tempdir.path(), &["commit", "-a", "-m", "unsorted"]);
+    assert!(!output.status.success());
+    assert_contains!(String::from_utf8(output.stderr).unwrap(), "Unsorted");
+
+    write(
+        tempdir.path(),
+        ".git/hooks/pre-commit",
+        "#!/bin/sh\nexec roast --staged\n",
+    );
+    let output = git(tempdir.path(), &["commit", "-a", "-m", "sorted"]);
+    assert!(output.status.success());
+
+    let committed = git(tempdir.path(), &["show", "HEAD:a.json"]);
+    assert_eq!(String::from_utf8(committed.stdout).unwrap(), SORTED);
+    assert_eq!(staged_contents(tempdir.path(), "a.json"), SORTED);
+    assert_eq!(read(tempdir.path(), "a.json"), SORTED);
+
+    Ok(())
+}
+
+#[test]
+fn staged_check() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "a.json", UNSORTED);
+    stage(&repo, "a.json");
+    write(tempdir.path(), "a.json", SORTED);
+
+    // the work tree is sorted, but the staged contents are not
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--staged")
+        .arg("--check")
+        .assert()
+        .code(3);
+
+    assert_eq!(staged_contents(tempdir.path(), "a.json"), UNSORTED);
+
+    Ok(())
+}
+
+#[test]
+fn staged_with_submodules() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--staged")
+        .arg("--recurseSubmodules")
+        .assert()
+        .code(2);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("cannot be used with"));
+
+    Ok(())
+}
+
+#[test]
+fn staged_outside_repo() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--staged")
+        .assert()
+        .code(1);
+
+    Ok(())
+}
+
+#[test]
+fn tracked_and_untracked() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), ".gitignore", "ignored.json\n");
+    write(tempdir.path(), "a.json", UNSORTED);
+    write(tempdir.path(), "dir/b.json", UNSORTED);
+    // git selects every tracked file, not only JSON files
+    write(tempdir.path(), "Cargo.toml", "[package]\n");
+    commit(&repo, "initial");
+    write(tempdir.path(), "new/c.json", UNSORTED);
+    write(tempdir.path(), "ignored.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--tracked")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "a.json");
+    assert_sorted(tempdir.path(), "dir/b.json");
+    assert_unsorted(tempdir.path(), "new/c.json");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--git")
+        .arg("--untracked")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "new/c.json");
+    assert_unsorted(tempdir.path(), "ignored.json");
+
+    Ok(())
+}
+
+#[test]
+fn since_ref() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "old.json", UNSORTED);
+    write(tempdir.path(), "unchanged.json", UNSORTED);
+    write(tempdir.path(), "deleted.json", UNSORTED);
+    commit(&repo, "initial");
+    repo.tag_lightweight(
+        "base",
+        &repo.head().unwrap().peel(git2::ObjectType::Commit).unwrap(),
+        false,
+    )
+    .unwrap();
+
+    fs::rename(
+        tempdir.path().join("old.json"),
+        tempdir.path().join("renamed.json"),
+    )
+    .unwrap();
+    fs::remove_file(tempdir.path().join("deleted.json")).unwrap();
+    write(tempdir.path(), "added.json", UNSORTED);
+    let mut index = repo.index().unwrap();
+    index.remove_path(Path::new("old.json")).unwrap();
+    index.remove_path(Path::new("deleted.json")).unwrap();
+    index.write().unwrap();
+    commit(&repo, "rename");
+    write(tempdir.path(), "uncommitted.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("--since")
+        .arg("base")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "renamed.json");
+    assert_sorted(tempdir.path(), "added.json");
+    assert_unsorted(tempdir.path(), "unchanged.json");
+    assert_unsorted(tempdir.path(), "uncommitted.json");
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert!(!stderr.contains("deleted.json"));
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--since")
+        .arg("no-such-ref")
+        .assert()
+        .code(1);
+
+    Ok(())
+}
+
+#[test]
+fn since_merge_base() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "main.json", UNSORTED);
+    commit(&repo, "initial");
+    git(tempdir.path(), &["branch", "base"]);
+    git(tempdir.path(), &["checkout", "-q", "-b", "feature"]);
+    write(tempdir.path(), "feature.json", UNSORTED);
+    commit(&repo, "feature");
+
+    // a change made only on the other branch after the feature branched off
+    git(tempdir.path(), &["checkout", "-q", "base"]);
+    write(tempdir.path(), "main.json", "{\"b\": 1}");
+    commit(&repo, "base");
+    git(tempdir.path(), &["checkout", "-q", "feature"]);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--since")
+        .arg("base")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "feature.json");
+    assert_unsorted(tempdir.path(), "main.json");
+
+    Ok(())
+}
+
+#[test]
+fn from_subdirectory() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "packages/api/a.json", "{}");
+    write(tempdir.path(), "b.json", "{}");
+    commit(&repo, "initial");
+    write(tempdir.path(), "packages/api/a.json", UNSORTED);
+    write(tempdir.path(), "b.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path().join("packages/api"))
+        .arg("--git")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "packages/api/a.json");
+    assert_sorted(tempdir.path(), "b.json");
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("./a.json - OK"));
+
+    Ok(())
+}
+
+#[test]
+fn linked_worktree() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let main = tempdir.path().join("main");
+    let linked = tempdir.path().join("linked");
+    let repo = Repository::init(&main).unwrap();
+    write(&main, "a.json", "{}");
+    commit(&repo, "initial");
+    repo.worktree("linked", &linked, None).unwrap();
+    write(&linked, "a.json", UNSORTED);
+    write(&main, "a.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(&linked).arg("--git").assert().success();
+
+    assert_sorted(&linked, "a.json");
+    assert_unsorted(&main, "a.json");
+
+    Ok(())
+}
+
+#[test]
+fn recurse_submodules() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let upstream = tempdir.path().join("upstream");
+    let parent = tempdir.path().join("parent");
+    let upstream_repo = Repository::init(&upstream).unwrap();
+    write(&upstream, "a.json", "{}");
+    commit(&upstream_repo, "initial");
+
+    let repo = Repository::init(&parent).unwrap();
+    let mut submodule = repo
+        .submodule(upstream.to_str().unwrap(), Path::new("sub"), true)
+        .unwrap();
+    submodule.clone(None).unwrap();
+    submodule.add_finalize().unwrap();
+    commit(&repo, "add submodule");
+    write(&parent, "sub/a.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(&parent).arg("--git").assert().success();
+
+    assert_unsorted(&parent, "sub/a.json");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(&parent)
+        .arg("--git")
+        .arg("--recurseSubmodules")
+        .assert()
+        .success();
+
+    assert_sorted(&parent, "sub/a.json");
+
+    Ok(())
+}
+
+#[test]
+fn gitattributes() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(
+        tempdir.path(),
+        ".gitattributes",
+        "generated.json linguist-generated\nkeep.json roast=false\nforced.json linguist-generated roast\nlists/*.json roast-arrays=true\n",
+    );
+    for name in ["generated.json", "keep.json", "forced.json", "a.json"] {
+        write(tempdir.path(), name, UNSORTED);
+    }
+    write(tempdir.path(), "lists/b.json", "[\"b\", \"a\"]");
+    commit(&repo, "initial");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg(".")
+        .arg("--lineEnding")
+        .arg("lf")
+        .assert()
+        .success();
+
+    assert_unsorted(tempdir.path(), "generated.json");
+    assert_unsorted(tempdir.path(), "keep.json");
+    assert_sorted(tempdir.path(), "forced.json");
+    assert_sorted(tempdir.path(), "a.json");
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("lists/b.json")).unwrap(),
+        "[\n\t\"a\",\n\t\"b\"\n]\n"
+    );
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("generated.json - Skipped, linguist-generated"));
+    assert!(stderr.contains("keep.json - Skipped, roast=false"));
+    assert!(stderr.contains("1 files skipped, linguist-generated\n"));
+    assert!(stderr.contains("1 files skipped, roast=false\n"));
+
+    // git modes also respect attributes
+    write(tempdir.path(), "keep.json", "{\"d\": 1, \"c\": 0}");
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--git")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("keep.json")).unwrap(),
+        "{\"d\": 1, \"c\": 0}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn only_changed() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    let original = "{\n\t\"z\": {\n\t\t\"b\": 1,\n\t\t\"a\": 0\n\t},\n\t\"y\": {\n\t\t\"d\": 1,\n\t\t\"c\": 0\n\t}\n}\n";
+    write(tempdir.path(), "legacy.json", original);
+    commit(&repo, "initial");
+
+    let changed = original.replace("\"d\": 1", "\"d\": 2");
+    write(tempdir.path(), "legacy.json", &changed);
+    write(tempdir.path(), "new.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--onlyChanged")
+        .arg("legacy.json")
+        .arg("new.json")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("legacy.json")).unwrap(),
+        "{\n\t\"z\": {\n\t\t\"b\": 1,\n\t\t\"a\": 0\n\t},\n\t\"y\": {\n\t\t\"c\": 0,\n\t\t\"d\": 2\n\t}\n}\n"
+    );
+    assert_sorted(tempdir.path(), "new.json");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--onlyChanged")
+        .arg("--git")
+        .arg("--check")
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn commit_sorted() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "roast").unwrap();
+    config.set_str("user.email", "roast@example.com").unwrap();
+    write(tempdir.path(), "unsorted.json", UNSORTED);
+    write(tempdir.path(), "sorted.json", SORTED);
+    write(
+        tempdir.path(),
+        ".git-blame-ignore-revs",
+        "# Earlier reformat\nabc123",
+    );
+    commit(&repo, "initial");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--tracked")
+        .arg("--commit")
+        .arg("--commitMessage")
+        .arg("Sort JSON")
+        .arg("--lineEnding")
+        .arg("lf")
+        .assert()
+        .success();
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let sort_commit = head.parent(0).unwrap();
+    assert_eq!(sort_commit.message(), Some("Sort JSON"));
+    let parent_tree = sort_commit.parent(0).unwrap().tree().unwrap();
+    let diff = repo
+        .diff_tree_to_tree(Some(&parent_tree), Some(&sort_commit.tree().unwrap()), None)
+        .unwrap();
+    let changed: Vec<_> = diff
+        .deltas()
+        .map(|d| d.new_file().path().unwrap().to_owned())
+        .collect();
+    assert_eq!(changed, vec![Path::new("unsorted.json")]);
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join(".git-blame-ignore-revs")).unwrap(),
+        format!(
+            "# Earlier reformat\nabc123\n# Sort JSON\n{}\n",
+            sort_commit.id()
+        )
+    );
+    let statuses = repo.statuses(None).unwrap();
+    assert!(statuses.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn commit_with_unstaged_changes() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "unsorted.json", UNSORTED);
+    write(tempdir.path(), "sorted.json", SORTED);
+    commit(&repo, "initial");
+    let edited = UNSORTED.replace("\"b\"", "\"edited\"");
+    write(tempdir.path(), "unsorted.json", &edited);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("--git")
+        .arg("--commit")
+        .assert()
+        .code(1);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "unsorted.json has uncommitted changes");
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("unsorted.json")).unwrap(),
+        edited
+    );
+
+    Ok(())
+}
+
+#[test]
+fn commit_with_staged_changes() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "unsorted.json", UNSORTED);
+    commit(&repo, "initial");
+    write(tempdir.path(), "other.txt", "unrelated");
+    stage(&repo, "other.txt");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("--tracked")
+        .arg("--commit")
+        .assert()
+        .code(1);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "the index contains staged changes");
+
+    assert_unsorted(tempdir.path(), "unsorted.json");
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use assert_cmd::Command;
+use git2::Repository;
+use std::fs;
+
+#[test]
+fn install() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    fs::write(tempdir.path().join(".gitattributes"), "*.png binary").unwrap();
+
+    for _ in 0..2 {
+        let mut cmd = Command::cargo_bin("roast").unwrap();
+        cmd.current_dir(tempdir.path())
+            .arg("filter")
+            .arg("install")
+            .assert()
+            .success();
+    }
+
+    let attributes = fs::read_to_string(tempdir.path().join(".gitattributes")).unwrap();
+    assert_eq!(attributes, "*.png binary\n*.json filter=roast\n");
+
+    let config = repo.config().unwrap().snapshot().unwrap();
+    assert_eq!(
+        config.get_str("filter.roast.process").unwrap(),
+        "roast filter process"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn install_keeps_unreadable_attributes() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    Repository::init(tempdir.path()).unwrap();
+    let invalid_utf8 = b"*.png binary\n# \xFF\n";
+    fs::write(tempdir.path().join(".gitattributes"), invalid_utf8).unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("filter")
+        .arg("install")
+        .assert()
+        .failure();
+
+    assert_eq!(
+        fs::read(tempdir.path().join(".gitattributes")).unwrap(),
+        invalid_utf8
+    );
+
+    Ok(())
+}
+
+#[test]
+fn process_handshake() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("filter")
+        .arg("process")
+        .write_stdin("0016git-filter-client\n000eversion=2\n00000015capability=clean\n0000")
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_eq!(
+        stdout,
+        "0016git-filter-server\n000eversion=2\n00000015capability=clean\n0000"
+    );
+
+    Ok(())
+}
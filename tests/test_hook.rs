@@ -0,0 +1,186 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use git2::Repository;
+use std::fs;
+use std::path::Path;
+
+mod common;
+use common::{commit, git, read, staged_contents, write, SORTED, UNSORTED};
+
+fn roast(dir: &Path, args: &[&str]) -> assert_cmd::assert::Assert {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(dir).arg("hook").args(args).assert()
+}
+
+#[test]
+fn install_and_uninstall() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    Repository::init(tempdir.path()).unwrap();
+    let hook = tempdir.path().join(".git/hooks/pre-commit");
+
+    roast(tempdir.path(), &["install"]).success();
+    let script = fs::read_to_string(&hook).unwrap();
+    assert_contains!(script, "roast --staged --check");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&hook).unwrap().permissions().mode();
+        assert_eq!(mode & 0o111, 0o111);
+    }
+
+    // installing again replaces the roast hook
+    roast(tempdir.path(), &["install", "--fix"]).success();
+    let script = fs::read_to_string(&hook).unwrap();
+    assert_contains!(script, "exec roast --staged\n");
+
+    roast(tempdir.path(), &["uninstall"]).success();
+    assert!(!hook.exists());
+
+    roast(tempdir.path(), &["uninstall"]).code(1);
+
+    Ok(())
+}
+
+#[test]
+fn foreign_hook() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    Repository::init(tempdir.path()).unwrap();
+    let hooks = tempdir.path().join(".git/hooks");
+    let foreign = "#!/bin/sh\necho foreign\n";
+    fs::create_dir_all(&hooks).unwrap();
+    fs::write(hooks.join("pre-commit"), foreign).unwrap();
+
+    let res = roast(tempdir.path(), &["install"]).code(1);
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "--chain");
+    assert_eq!(
+        fs::read_to_string(hooks.join("pre-commit")).unwrap(),
+        foreign
+    );
+
+    let res = roast(tempdir.path(), &["uninstall"]).code(1);
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "was not installed by roast");
+    assert!(!stderr.contains("--chain"));
+    assert_eq!(
+        fs::read_to_string(hooks.join("pre-commit")).unwrap(),
+        foreign
+    );
+
+    roast(tempdir.path(), &["install", "--chain"]).success();
+    let script = fs::read_to_string(hooks.join("pre-commit")).unwrap();
+    assert_contains!(script, "pre-commit.chained");
+    assert_eq!(
+        fs::read_to_string(hooks.join("pre-commit.chained")).unwrap(),
+        foreign
+    );
+
+    roast(tempdir.path(), &["uninstall"]).success();
+    assert_eq!(
+        fs::read_to_string(hooks.join("pre-commit")).unwrap(),
+        foreign
+    );
+    assert!(!hooks.join("pre-commit.chained").exists());
+
+    Ok(())
+}
+
+#[test]
+fn hooks_path() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("core.hooksPath", ".githooks")
+        .unwrap();
+    fs::create_dir_all(tempdir.path().join("sub")).unwrap();
+
+    roast(&tempdir.path().join("sub"), &["install"]).success();
+
+    assert!(tempdir.path().join(".githooks/pre-commit").exists());
+    assert!(!tempdir.path().join(".git/hooks/pre-commit").exists());
+
+    Ok(())
+}
+
+#[test]
+fn outside_repo() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+
+    let res = roast(tempdir.path(), &["install"]).code(1);
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "not a git repository");
+
+    Ok(())
+}
+
+#[test]
+fn check_commit_all_and_paths() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "a.json", SORTED);
+    commit(&repo, "initial");
+    roast(tempdir.path(), &["install"]).success();
+    write(tempdir.path(), "a.json", UNSORTED);
+
+    for args in [
+        &["commit", "-a", "-m", "all"][..],
+        &["commit", "-m", "paths", "a.json"][..],
+    ] {
+        let output = git(tempdir.path(), args);
+        assert!(!output.status.success(), "{args:?} was committed");
+        assert_contains!(String::from_utf8(output.stderr).unwrap(), "Unsorted");
+    }
+
+    let committed = git(tempdir.path(), &["show", "HEAD:a.json"]);
+    assert_eq!(String::from_utf8(committed.stdout).unwrap(), SORTED);
+
+    Ok(())
+}
+
+#[test]
+fn fix_commit_all() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "a.json", SORTED);
+    commit(&repo, "initial");
+    roast(tempdir.path(), &["install", "--fix"]).success();
+    write(tempdir.path(), "a.json", UNSORTED);
+
+    let output = git(tempdir.path(), &["commit", "-a", "-m", "all"]);
+    assert!(output.status.success());
+
+    let committed = git(tempdir.path(), &["show", "HEAD:a.json"]);
+    assert_eq!(String::from_utf8(committed.stdout).unwrap(), SORTED);
+    assert_eq!(staged_contents(tempdir.path(), "a.json"), SORTED);
+    assert_eq!(read(tempdir.path(), "a.json"), SORTED);
+
+    Ok(())
+}
+
+#[test]
+fn fix_commit_paths() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), "a.json", SORTED);
+    write(tempdir.path(), "b.json", SORTED);
+    commit(&repo, "initial");
+    roast(tempdir.path(), &["install", "--fix"]).success();
+    write(tempdir.path(), "a.json", UNSORTED);
+    write(tempdir.path(), "b.json", UNSORTED);
+
+    let output = git(tempdir.path(), &["commit", "-m", "paths", "a.json"]);
+    assert!(output.status.success());
+
+    let committed = git(tempdir.path(), &["show", "HEAD:a.json"]);
+    assert_eq!(String::from_utf8(committed.stdout).unwrap(), SORTED);
+    assert_eq!(read(tempdir.path(), "a.json"), SORTED);
+
+    // files outside the pathspec are not committed or sorted
+    let committed = git(tempdir.path(), &["show", "HEAD:b.json"]);
+    assert_eq!(String::from_utf8(committed.stdout).unwrap(), SORTED);
+    assert_eq!(read(tempdir.path(), "b.json"), UNSORTED);
+
+    Ok(())
+}
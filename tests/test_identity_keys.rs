@@ -0,0 +1,89 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use std::fs;
+
+#[test]
+fn sort_by_identity_keys() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--identityKeys")
+        .arg("--verbose")
+        .arg("--lineEnding")
+        .arg("lf")
+        .write_stdin(
+            r#"{
+                "users": [{"id": 2, "name": "a"}, {"id": 1, "name": "b"}],
+                "tags": [{"name": "b"}, {"name": "a"}],
+                "points": [{"x": 2}, {"x": 1}],
+                "mixed": [{"id": 2}, {"id": 2}]
+            }"#,
+        )
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    let sorted: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        sorted,
+        serde_json::json!({
+            "mixed": [{"id": 2}, {"id": 2}],
+            "points": [{"x": 2}, {"x": 1}],
+            "tags": [{"name": "a"}, {"name": "b"}],
+            "users": [{"id": 1, "name": "b"}, {"id": 2, "name": "a"}]
+        })
+    );
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, r#"Sorted array at $.users by identity key "id""#);
+    assert_contains!(stderr, r#"Sorted array at $.tags by identity key "name""#);
+    assert_contains!(stderr, "Not sorting array at $.points, no identity key");
+    assert_contains!(stderr, "Not sorting array at $.mixed, no identity key");
+
+    Ok(())
+}
+
+#[test]
+fn identity_keys_from_config() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("roast.toml"), "identityKeys = true\n").unwrap();
+    fs::write(
+        tempdir.path().join("a.json"),
+        r#"[{"key": "b"}, {"key": "a"}]"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--lineEnding")
+        .arg("lf")
+        .arg("a.json")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("a.json")).unwrap(),
+        "[\n\t{\n\t\t\"key\": \"a\"\n\t},\n\t{\n\t\t\"key\": \"b\"\n\t}\n]\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn identity_keys_are_opt_in() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--arrays")
+        .arg("--lineEnding")
+        .arg("lf")
+        .write_stdin(r#"[{"id": 2}, {"id": 1}]"#)
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_eq!(
+        stdout,
+        "[\n\t{\n\t\t\"id\": 2\n\t},\n\t{\n\t\t\"id\": 1\n\t}\n]\n"
+    );
+
+    Ok(())
+}
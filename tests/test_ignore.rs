@@ -0,0 +1,172 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+
+mod common;
+use common::{assert_sorted, assert_unsorted, write, UNSORTED};
+
+#[test]
+fn component_matching() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "CVSreports/a.json", UNSORTED);
+    write(tempdir.path(), "node_modules_fan/a.json", UNSORTED);
+    write(tempdir.path(), "CVS/a.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path()).arg(".").assert().success();
+
+    assert_sorted(tempdir.path(), "CVSreports/a.json");
+    assert_sorted(tempdir.path(), "node_modules_fan/a.json");
+    assert_unsorted(tempdir.path(), "CVS/a.json");
+
+    Ok(())
+}
+
+#[test]
+fn exclude_and_include() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "a.json", UNSORTED);
+    write(tempdir.path(), "b.min.json", UNSORTED);
+    write(tempdir.path(), "fixtures/c.json", UNSORTED);
+    write(tempdir.path(), "package.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg(".")
+        .arg("--exclude")
+        .arg("*.min.json")
+        .arg("--exclude")
+        .arg("./fixtures/")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "a.json");
+    assert_unsorted(tempdir.path(), "b.min.json");
+    assert_unsorted(tempdir.path(), "fixtures/c.json");
+    assert_unsorted(tempdir.path(), "package.json");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg(".")
+        .arg("--include")
+        .arg("package.json")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "package.json");
+    assert_unsorted(tempdir.path(), "b.min.json");
+
+    Ok(())
+}
+
+#[test]
+fn invalid_glob() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd.arg(".").arg("--exclude").arg("a[").assert().code(2);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "invalid glob pattern");
+
+    Ok(())
+}
+
+#[test]
+fn roastignore() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), ".roastignore", "generated/\n*.lock.json\n");
+    write(tempdir.path(), "a.json", UNSORTED);
+    write(tempdir.path(), "b.lock.json", UNSORTED);
+    write(tempdir.path(), "sub/generated/c.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path()).arg(".").assert().success();
+
+    assert_sorted(tempdir.path(), "a.json");
+    assert_unsorted(tempdir.path(), "b.lock.json");
+    assert_unsorted(tempdir.path(), "sub/generated/c.json");
+
+    Ok(())
+}
+
+#[test]
+fn gitignore() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    git2::Repository::init(tempdir.path()).unwrap();
+    write(tempdir.path(), ".gitignore", "build/\n");
+    write(tempdir.path(), ".git/info/exclude", "local.json\n");
+    write(tempdir.path(), "a.json", UNSORTED);
+    write(tempdir.path(), "local.json", UNSORTED);
+    write(tempdir.path(), "build/b.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd.current_dir(tempdir.path()).arg(".").assert().success();
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert!(!stderr.contains(".git/"));
+
+    assert_sorted(tempdir.path(), "a.json");
+    assert_unsorted(tempdir.path(), "local.json");
+    assert_unsorted(tempdir.path(), "build/b.json");
+
+    Ok(())
+}
+
+#[test]
+fn glob_arguments() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "config/b.json", UNSORTED);
+    write(tempdir.path(), "config/a/a.json", UNSORTED);
+    write(tempdir.path(), "config/fixtures/c.json", UNSORTED);
+    write(tempdir.path(), "i18n/de.json", UNSORTED);
+    write(tempdir.path(), "i18n/all.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("config/**/*.json")
+        .arg("!**/fixtures/**")
+        .arg("i18n/[a-z][a-z].json")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "config/b.json");
+    assert_sorted(tempdir.path(), "config/a/a.json");
+    assert_unsorted(tempdir.path(), "config/fixtures/c.json");
+    assert_sorted(tempdir.path(), "i18n/de.json");
+    assert_unsorted(tempdir.path(), "i18n/all.json");
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    let first = stderr.find("config/a/a.json").unwrap();
+    let second = stderr.find("config/b.json").unwrap();
+    assert!(first < second);
+
+    Ok(())
+}
+
+#[test]
+fn only_negations() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "a.json", UNSORTED);
+    write(tempdir.path(), "fixtures/b.json", UNSORTED);
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("!fixtures")
+        .assert()
+        .success();
+
+    assert_sorted(tempdir.path(), "a.json");
+    assert_unsorted(tempdir.path(), "fixtures/b.json");
+
+    Ok(())
+}
+
+#[test]
+fn invalid_glob_argument() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd.arg("config/a[.json").assert().code(2);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "invalid glob pattern");
+
+    Ok(())
+}
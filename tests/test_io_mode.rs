@@ -57,3 +57,17 @@ fn buffered_mode_invalid_input() -> Result<(), String> {
 
     Ok(())
 }
+
+#[test]
+fn buffered_mode_invalid_input_location() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd.write_stdin("{\n  \"a\": 1,\n}").assert().failure();
+
+    let out = res.get_output();
+    let stderr = String::from_utf8(out.stderr.clone()).unwrap();
+
+    assert_contains!(stderr, "trailing comma at line 3 column 1");
+    assert_contains!(stderr, "> 3 | }\n    | ^");
+
+    Ok(())
+}
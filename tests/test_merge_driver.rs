@@ -0,0 +1,99 @@
+use assert_cmd::Command;
+use std::fs;
+
+mod common;
+use common::write;
+
+#[test]
+fn clean_merge() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "base", "{\"a\": 1}");
+    write(tempdir.path(), "ours", "{\"a\": 1, \"c\": 3}\n");
+    write(tempdir.path(), "theirs", "{\"b\": 2, \"a\": 1}");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .args(["merge-driver", "base", "ours", "theirs", "i18n/en.json"])
+        .assert()
+        .success();
+
+    let merged = fs::read_to_string(tempdir.path().join("ours")).unwrap();
+    assert_eq!(merged, "{\n\t\"a\": 1,\n\t\"b\": 2,\n\t\"c\": 3\n}\n");
+
+    Ok(())
+}
+
+#[test]
+fn conflict() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "base", "{\"a\": 1}");
+    write(tempdir.path(), "ours", "{\"a\": 2}\n");
+    write(tempdir.path(), "theirs", "{\"a\": 3}\n");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .args(["merge-driver", "base", "ours", "theirs"])
+        .assert()
+        .code(1);
+
+    let merged = fs::read_to_string(tempdir.path().join("ours")).unwrap();
+    assert_eq!(
+        merged,
+        "{\n<<<<<<< ours\n\t\"a\": 2\n=======\n\t\"a\": 3\n>>>>>>> theirs\n}\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn conflict_with_deleted_keys() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "base", "{\"a\": 1, \"b\": 1, \"c\": 1}");
+    write(tempdir.path(), "ours", "{\"a\": 1}\n");
+    write(tempdir.path(), "theirs", "{\"a\": 1, \"b\": 2, \"c\": 2}\n");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .args(["merge-driver", "base", "ours", "theirs"])
+        .assert()
+        .code(1);
+
+    let merged = fs::read_to_string(tempdir.path().join("ours")).unwrap();
+    assert_eq!(
+        merged,
+        "{\n<<<<<<< ours\n\t\"a\": 1\n=======\n\t\"a\": 1,\n\t\"b\": 2,\n\t\"c\": 2\n>>>>>>> theirs\n}\n"
+    );
+
+    // taking either side leaves valid JSON
+    let (start, rest) = merged.split_once("<<<<<<< ours\n").unwrap();
+    let (ours, rest) = rest.split_once("=======\n").unwrap();
+    let (theirs, end) = rest.split_once(">>>>>>> theirs\n").unwrap();
+    for side in [ours, theirs] {
+        let resolved = format!("{start}{side}{end}");
+        assert!(
+            serde_json::from_str::<serde_json::Value>(&resolved).is_ok(),
+            "{resolved}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn parse_error_keeps_ours() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    write(tempdir.path(), "base", "{\"a\": 1}");
+    write(tempdir.path(), "ours", "{\"a\": 2,}");
+    write(tempdir.path(), "theirs", "{\"a\": 3}");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .args(["merge-driver", "base", "ours", "theirs"])
+        .assert()
+        .code(6);
+
+    let ours = fs::read_to_string(tempdir.path().join("ours")).unwrap();
+    assert_eq!(ours, "{\"a\": 2,}");
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use assert_cmd::Command;
+use std::fs;
+
+#[test]
+fn natural_keys_and_arrays() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--natural")
+        .arg("--arrays")
+        .arg("--lineEnding")
+        .arg("lf")
+        .write_stdin(r#"{"item10": 1, "item2": 1, "versions": ["v1.10", "v1.9", "v1.2"]}"#)
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_eq!(
+        stdout,
+        "{\n\t\"item2\": 1,\n\t\"item10\": 1,\n\t\"versions\": [\n\t\t\"v1.2\",\n\t\t\"v1.9\",\n\t\t\"v1.10\"\n\t]\n}\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn natural_from_config() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("roast.toml"), "natural = true\n").unwrap();
+    fs::write(tempdir.path().join("a.json"), r#"{"a10": 1, "a9": 1}"#).unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--lineEnding")
+        .arg("lf")
+        .arg("a.json")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("a.json")).unwrap(),
+        "{\n\t\"a9\": 1,\n\t\"a10\": 1\n}\n"
+    );
+
+    Ok(())
+}
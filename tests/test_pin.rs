@@ -0,0 +1,135 @@
+use assert_cmd::Command;
+use assertables::assert_contains;
+use std::fs;
+
+static CONFIG: &str = r#"
+spaces = true
+
+[[pin]]
+first = ["$schema", "$id", "name", "version", "type"]
+last = ["/^x-/"]
+
+[[pin]]
+path = "$.properties.*"
+first = ["description"]
+"#;
+
+#[test]
+fn pin_from_config() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("roast.toml"), CONFIG).unwrap();
+    fs::write(
+        tempdir.path().join("a.schema.json"),
+        r#"{"x-order": 1, "properties": {"b": {"type": "string", "description": "B", "default": ""}}, "type": "object", "$schema": "s", "additionalProperties": false}"#,
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("--lineEnding")
+        .arg("lf")
+        .arg("a.schema.json")
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(tempdir.path().join("a.schema.json")).unwrap(),
+        r#"{
+  "$schema": "s",
+  "type": "object",
+  "additionalProperties": false,
+  "properties": {
+    "b": {
+      "description": "B",
+      "type": "string",
+      "default": ""
+    }
+  },
+  "x-order": 1
+}
+"#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn pin_from_cli() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--pinFirst")
+        .arg("version")
+        .arg("--pinFirst")
+        .arg("name")
+        .arg("--pinLast")
+        .arg("/^_/")
+        .arg("--lineEnding")
+        .arg("lf")
+        .write_stdin(r#"{"_b": 1, "_a": 1, "a": 1, "name": 1, "version": 1}"#)
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_eq!(
+        stdout,
+        "{\n\t\"version\": 1,\n\t\"name\": 1,\n\t\"a\": 1,\n\t\"_a\": 1,\n\t\"_b\": 1\n}\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn print_pins() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(tempdir.path().join("roast.toml"), CONFIG).unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("print-config")
+        .arg("a.json")
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_contains!(
+        stdout,
+        "[[pin]]\nfirst = [\"$schema\", \"$id\", \"name\", \"version\", \"type\"]\nlast = [\"/^x-/\"]\n"
+    );
+    assert_contains!(
+        stdout,
+        "[[pin]]\npath = \"$.properties.*\"\nfirst = [\"description\"]\nlast = []\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn invalid_pin() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    fs::write(
+        tempdir.path().join("roast.toml"),
+        "[[pin]]\nfirst = [\"/[/\"]\n",
+    )
+    .unwrap();
+    fs::write(tempdir.path().join("a.json"), "{}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .current_dir(tempdir.path())
+        .arg("a.json")
+        .assert()
+        .code(9);
+
+    let stderr = String::from_utf8(res.get_output().stderr.clone()).unwrap();
+    assert_contains!(stderr, "invalid key pattern /[/");
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg("--pinFirst")
+        .arg("/(/")
+        .arg("a.json")
+        .assert()
+        .code(2);
+
+    Ok(())
+}
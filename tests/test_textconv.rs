@@ -0,0 +1,82 @@
+use assert_cmd::Command;
+use git2::Repository;
+use std::fs;
+
+#[test]
+fn textconv() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("minified.json");
+    fs::write(&path, "{\"b\":[2,1],\"a\":{\"d\":1,\"c\":0}}").unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd
+        .arg("--spaces")
+        .arg("textconv")
+        .arg(&path)
+        .assert()
+        .success();
+
+    let stdout = String::from_utf8(res.get_output().stdout.clone()).unwrap();
+    assert_eq!(
+        stdout,
+        "{\n  \"a\": {\n    \"c\": 0,\n    \"d\": 1\n  },\n  \"b\": [\n    2,\n    1\n  ]\n}\n"
+    );
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "{\"b\":[2,1],\"a\":{\"d\":1,\"c\":0}}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn textconv_not_json() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("broken.json");
+    let contents = b"{\"b\": 1,\xff";
+    fs::write(&path, contents).unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    let res = cmd.arg("textconv").arg(&path).assert().success();
+
+    assert_eq!(res.get_output().stdout, contents);
+
+    Ok(())
+}
+
+#[test]
+fn install() -> Result<(), String> {
+    let tempdir = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tempdir.path()).unwrap();
+    fs::write(
+        tempdir.path().join(".gitattributes"),
+        "*.json filter=roast\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.current_dir(tempdir.path())
+        .arg("textconv")
+        .arg("--install")
+        .assert()
+        .success();
+
+    let attributes = fs::read_to_string(tempdir.path().join(".gitattributes")).unwrap();
+    assert_eq!(attributes, "*.json filter=roast\n*.json diff=roast\n");
+
+    let config = repo.config().unwrap().snapshot().unwrap();
+    assert_eq!(
+        config.get_str("diff.roast.textconv").unwrap(),
+        "roast textconv"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn textconv_requires_file() -> Result<(), String> {
+    let mut cmd = Command::cargo_bin("roast").unwrap();
+    cmd.arg("textconv").assert().code(2);
+
+    Ok(())
+}